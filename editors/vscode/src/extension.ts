@@ -90,6 +90,16 @@ export async function activate(context: ExtensionContext): Promise<void> {
 
   const clientOptions: LanguageClientOptions = {
     documentSelector: [{ scheme: "file", language: "markdown" }],
+    initializationOptions: {
+      tagFields: config.get("tagFields", []),
+      aliasesField: config.get("aliasesField", "aliases"),
+      linkTieBreak: config.get("linkTieBreak", ["sameFolder", "shortestPath"]),
+      linkStyle: config.get("linkStyle", null),
+      linkExtension: config.get("linkExtension", null),
+    },
+    synchronize: {
+      configurationSection: "mdbase",
+    },
   };
 
   client = new LanguageClient(
@@ -265,6 +275,88 @@ export async function activate(context: ExtensionContext): Promise<void> {
       validationOutput.show();
     })
   );
+
+  context.subscriptions.push(
+    commands.registerCommand("mdbase.splitNote", async () => {
+      const editor = window.activeTextEditor;
+      if (!client || !editor) {
+        return;
+      }
+      const level = await window.showQuickPick(["1", "2", "3", "4", "5", "6"], {
+        placeHolder: "Split at headings of level",
+      });
+      if (!level) {
+        return;
+      }
+
+      // Offer the note's top-level frontmatter keys for inheritance.
+      const text = editor.document.getText();
+      const frontmatter = /^---\r?\n([\s\S]*?)\r?\n---/.exec(text)?.[1] ?? "";
+      const keys = Array.from(
+        frontmatter.matchAll(/^([A-Za-z_][\w-]*)\s*:/gm),
+        (m) => m[1]
+      ).filter((key) => key !== "title");
+      let inherit: string[] = [];
+      if (keys.length > 0) {
+        const picked = await window.showQuickPick(keys, {
+          canPickMany: true,
+          placeHolder: "Fields the new notes inherit",
+        });
+        if (picked === undefined) {
+          return;
+        }
+        inherit = picked;
+      }
+
+      const replaceWith = await window.showQuickPick(["Links", "Embeds"], {
+        placeHolder: "Replace sections in this note with",
+      });
+      if (!replaceWith) {
+        return;
+      }
+
+      await client.sendRequest(ExecuteCommandRequest.type, {
+        command: "mdbase.splitNote",
+        arguments: [
+          {
+            uri: editor.document.uri.toString(),
+            level: Number(level),
+            inherit,
+            embed: replaceWith === "Embeds",
+          },
+        ],
+      });
+    })
+  );
+
+  context.subscriptions.push(
+    commands.registerCommand("mdbase.mergeNotes", async () => {
+      const editor = window.activeTextEditor;
+      if (!client || !editor) {
+        return;
+      }
+      const source = editor.document.uri;
+      const candidates = (await workspace.findFiles("**/*.md", "**/_types/**"))
+        .filter((uri) => uri.toString() !== source.toString())
+        .map((uri) => ({ label: workspace.asRelativePath(uri), uri }));
+      const destination = await window.showQuickPick(candidates, {
+        placeHolder: `Merge ${workspace.asRelativePath(source)} into`,
+      });
+      if (!destination) {
+        return;
+      }
+
+      await client.sendRequest(ExecuteCommandRequest.type, {
+        command: "mdbase.mergeNotes",
+        arguments: [
+          {
+            source: source.toString(),
+            destination: destination.uri.toString(),
+          },
+        ],
+      });
+    })
+  );
 }
 
 export async function deactivate(): Promise<void> {
@@ -0,0 +1,135 @@
+/// Call hierarchy provider — exposes the note link graph as a navigable tree.
+///
+/// Notes map onto call hierarchy items: incoming calls are the notes that link
+/// to an item (backlinks) and outgoing calls are the notes it links to.
+use tower_lsp::lsp_types::*;
+
+use crate::collection_utils;
+use crate::link_resolve;
+use crate::references;
+use crate::state::BackendState;
+
+/// Build the `textDocument/prepareCallHierarchy` response.
+///
+/// When the cursor sits on a link the item is the link's target; otherwise it
+/// is the current note.
+pub(crate) fn prepare(
+    state: &BackendState,
+    params: CallHierarchyPrepareParams,
+) -> Option<Vec<CallHierarchyItem>> {
+    let collection = state.get_collection()?;
+    let uri = &params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+    let text = state.document_text(uri)?;
+    let source_rel = collection_utils::rel_path_from_uri(&collection, uri)?;
+
+    let target_rel =
+        references::symbol_at_position(state, &collection, &text, &source_rel, position)
+            .map(|symbol| symbol.target)
+            .unwrap_or(source_rel);
+    Some(vec![item_for(state, &collection, &target_rel)?])
+}
+
+/// Build the `callHierarchy/incomingCalls` response: every note linking to
+/// the item, with the ranges of those links in the linking note.
+pub(crate) fn incoming(
+    state: &BackendState,
+    params: CallHierarchyIncomingCallsParams,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let collection = state.get_collection()?;
+    let target_rel = collection_utils::rel_path_from_uri(&collection, &params.item.uri)?;
+
+    let resolver = link_resolve::IndexResolver::for_request(state, &collection);
+    let mut calls = Vec::new();
+    for (_, rel_path, text) in collection_utils::collection_texts(state, &collection) {
+        if rel_path == target_rel {
+            continue;
+        }
+        let refs = references::find_references_in_text(&resolver, &text, &rel_path, &target_rel);
+        if refs.is_empty() {
+            continue;
+        }
+        let Some(from) = item_for(state, &collection, &rel_path) else {
+            continue;
+        };
+        calls.push(CallHierarchyIncomingCall {
+            from,
+            from_ranges: refs.into_iter().map(|r| r.range).collect(),
+        });
+    }
+    Some(calls)
+}
+
+/// Build the `callHierarchy/outgoingCalls` response: every note the item
+/// links to, with the ranges of those links in the item itself.
+pub(crate) fn outgoing(
+    state: &BackendState,
+    params: CallHierarchyOutgoingCallsParams,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let collection = state.get_collection()?;
+    let source_rel = collection_utils::rel_path_from_uri(&collection, &params.item.uri)?;
+    let text = state
+        .document_text(&params.item.uri)
+        .or_else(|| std::fs::read_to_string(collection.root.join(&source_rel)).ok())?;
+
+    // Group link ranges by target, keeping first-seen order.
+    let mut grouped: Vec<(String, Vec<Range>)> = Vec::new();
+    let resolver = link_resolve::IndexResolver::for_request(state, &collection);
+    for (rel, found) in references::resolved_links_in_text(&resolver, &text, &source_rel) {
+        if rel == source_rel {
+            continue;
+        }
+        match grouped.iter_mut().find(|(r, _)| *r == rel) {
+            Some((_, ranges)) => ranges.push(found.range),
+            None => grouped.push((rel, vec![found.range])),
+        }
+    }
+
+    Some(
+        grouped
+            .into_iter()
+            .filter_map(|(rel, from_ranges)| {
+                Some(CallHierarchyOutgoingCall {
+                    to: item_for(state, &collection, &rel)?,
+                    from_ranges,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Build a call hierarchy item for a collection-relative note path, using the
+/// file index for its display name and types.
+fn item_for(
+    state: &BackendState,
+    collection: &mdbase::Collection,
+    rel_path: &str,
+) -> Option<CallHierarchyItem> {
+    let uri = collection_utils::uri_from_rel_path(collection, rel_path)?;
+    let entry = state.file_index.entry(rel_path);
+    let name = entry
+        .as_ref()
+        .and_then(|e| e.display_name.clone())
+        .unwrap_or_else(|| {
+            std::path::Path::new(rel_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(rel_path)
+                .to_string()
+        });
+    let detail = match &entry {
+        Some(e) if !e.types.is_empty() => format!("{} ({})", rel_path, e.types.join(", ")),
+        _ => rel_path.to_string(),
+    };
+    let range = Range::new(Position::new(0, 0), Position::new(0, 0));
+    Some(CallHierarchyItem {
+        name,
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: Some(detail),
+        uri,
+        range,
+        selection_range: range,
+        data: Some(serde_json::json!({ "path": rel_path })),
+    })
+}
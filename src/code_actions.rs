@@ -3,6 +3,8 @@ use tower_lsp::lsp_types::*;
 use mdbase::types::schema::FieldDef;
 
 use crate::collection_utils;
+use crate::extract_note;
+use crate::link_fixes;
 use crate::state::BackendState;
 use crate::text;
 
@@ -30,6 +32,26 @@ pub(crate) fn provide(
         if diagnostic.source.as_deref() != Some("mdbase") {
             continue;
         }
+        if let Some(date_actions) = normalize_date_actions(uri, diagnostic) {
+            actions.extend(date_actions);
+            continue;
+        }
+        if let Some(candidate_actions) = ambiguous_link_actions(uri, diagnostic) {
+            actions.extend(candidate_actions);
+            continue;
+        }
+        let link_actions = link_fixes::unresolved_link_actions(
+            state,
+            &collection,
+            uri,
+            &doc_text,
+            &rel_path,
+            diagnostic,
+        );
+        if !link_actions.is_empty() {
+            actions.extend(link_actions);
+            continue;
+        }
         let field = diagnostic
             .data
             .as_ref()
@@ -38,6 +60,10 @@ pub(crate) fn provide(
         let Some(field_name) = field else {
             continue;
         };
+        // Nested paths (`address.city`) can't be written as a top-level line.
+        if field_name.contains(['.', '[']) {
+            continue;
+        }
 
         // Always offer to insert missing field.
         actions.push(CodeActionOrCommand::CodeAction(CodeAction {
@@ -87,6 +113,26 @@ pub(crate) fn provide(
         }
     }
 
+    // Extracting builds a full edit per type, so only do it when the client
+    // asks for refactorings.
+    let wants_extract = params.context.only.as_ref().is_some_and(|only| {
+        only.iter().any(|kind| {
+            CodeActionKind::REFACTOR_EXTRACT
+                .as_str()
+                .starts_with(kind.as_str())
+        })
+    });
+    if wants_extract {
+        actions.extend(extract_note::actions(
+            state,
+            &collection,
+            uri,
+            &doc_text,
+            &rel_path,
+            params.range,
+        ));
+    }
+
     if actions.is_empty() {
         None
     } else {
@@ -94,6 +140,78 @@ pub(crate) fn provide(
     }
 }
 
+/// Quick fixes for a `non_iso_date` diagnostic: replace the value with one of
+/// the ISO readings carried in the diagnostic data. An ambiguous value gets
+/// one fix per reading and none is preferred.
+fn normalize_date_actions(uri: &Url, diagnostic: &Diagnostic) -> Option<Vec<CodeActionOrCommand>> {
+    if diagnostic.code != Some(NumberOrString::String("non_iso_date".to_string())) {
+        return None;
+    }
+    let readings = diagnostic
+        .data
+        .as_ref()
+        .and_then(|d| d.get("normalized"))
+        .and_then(|v| v.as_array())?;
+    let preferred = readings.len() == 1;
+    let actions = readings
+        .iter()
+        .filter_map(|reading| reading.as_str())
+        .map(|normalized| {
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Normalize date to '{}'", normalized),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(workspace_edit_for(
+                    uri.clone(),
+                    TextEdit {
+                        range: diagnostic.range,
+                        new_text: normalized.to_string(),
+                    },
+                )),
+                is_preferred: Some(preferred),
+                ..Default::default()
+            })
+        })
+        .collect();
+    Some(actions)
+}
+
+/// One quick fix per candidate of an ambiguous link, rewriting it to that
+/// note's path. The first candidate (the current resolution) is preferred.
+fn ambiguous_link_actions(uri: &Url, diagnostic: &Diagnostic) -> Option<Vec<CodeActionOrCommand>> {
+    if diagnostic.code != Some(NumberOrString::String("ambiguous_link".to_string())) {
+        return None;
+    }
+    let candidates = diagnostic
+        .data
+        .as_ref()
+        .and_then(|d| d.get("candidates"))
+        .and_then(|v| v.as_array())?;
+    let actions = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, candidate)| {
+            let path = candidate.get("path")?.as_str()?;
+            let new_text = candidate.get("newText")?.as_str()?;
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Link to '{}'", path),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(workspace_edit_for(
+                    uri.clone(),
+                    TextEdit {
+                        range: diagnostic.range,
+                        new_text: new_text.to_string(),
+                    },
+                )),
+                is_preferred: Some(idx == 0),
+                ..Default::default()
+            }))
+        })
+        .collect();
+    Some(actions)
+}
+
 fn workspace_edit_for(uri: Url, edit: TextEdit) -> WorkspaceEdit {
     WorkspaceEdit {
         changes: Some(std::collections::HashMap::from([(uri, vec![edit])])),
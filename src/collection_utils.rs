@@ -1,15 +1,47 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use mdbase::types::schema::FieldDef;
 use mdbase::Collection;
-use tower_lsp::lsp_types::Url;
+use tower_lsp::lsp_types::{
+    AnnotatedTextEdit, DocumentChangeOperation, OneOf, OptionalVersionedTextDocumentIdentifier,
+    TextDocumentEdit, TextEdit, Url,
+};
 use tracing::debug;
 
+use crate::config::LinkTieBreak;
+use crate::state::BackendState;
+
 pub(crate) fn scan_collection_files(collection: &Collection) -> Vec<PathBuf> {
     let mut files = Vec::new();
     scan_dir_recursive(collection, &collection.root, &mut files);
     files
 }
 
+/// Every collection file as `(uri, rel_path, text)`, taking the open buffer
+/// over the file on disk. Files are read as the iterator advances.
+pub(crate) fn collection_texts<'a>(
+    state: &'a BackendState,
+    collection: &'a Collection,
+) -> impl Iterator<Item = (Url, String, String)> + 'a {
+    scan_collection_files(collection)
+        .into_iter()
+        .filter_map(move |path| {
+            let uri = Url::from_file_path(&path).ok()?;
+            let rel_path = path
+                .strip_prefix(&collection.root)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let text = state
+                .document_text(&uri)
+                .or_else(|| std::fs::read_to_string(&path).ok())
+                .unwrap_or_default();
+            Some((uri, rel_path, text))
+        })
+}
+
 pub(crate) fn find_type_definition_path(
     collection: &Collection,
     type_name: &str,
@@ -29,6 +61,173 @@ pub(crate) fn find_type_definition_path(
     None
 }
 
+/// Return the type name defined by a file inside the types folder, if any.
+pub(crate) fn type_name_for_uri(collection: &Collection, uri: &Url) -> Option<String> {
+    let path = uri.to_file_path().ok()?;
+    let types_dir = collection.root.join(&collection.settings.types_folder);
+    if !path.starts_with(&types_dir) {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?.to_lowercase();
+    if collection.types.contains_key(&stem) {
+        Some(stem)
+    } else {
+        None
+    }
+}
+
+/// Whether `field_name` holds type names in notes (`type`, `types`, or one
+/// of the collection's explicit type keys).
+pub(crate) fn is_type_key(collection: &Collection, field_name: &str) -> bool {
+    field_name == "type"
+        || field_name == "types"
+        || collection
+            .settings
+            .explicit_type_keys
+            .iter()
+            .any(|k| k == field_name)
+}
+
+/// Names of the types whose `extends` points directly at `type_name`.
+pub(crate) fn child_types(collection: &Collection, type_name: &str) -> Vec<String> {
+    let mut children: Vec<String> = collection
+        .types
+        .iter()
+        .filter(|(_, def)| {
+            def.extends
+                .as_deref()
+                .is_some_and(|parent| parent.eq_ignore_ascii_case(type_name))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    children.sort();
+    children
+}
+
+/// Field declarations read from the type definition files.
+///
+/// Loaded type definitions include inherited fields, so the definition files
+/// are consulted to tell a declaration apart from an inherited field. The
+/// types folder is listed once and each file parsed at most once, so build
+/// one per request.
+pub(crate) struct TypeDeclarations<'a> {
+    collection: &'a Collection,
+    paths: HashMap<String, PathBuf>,
+    declared: RefCell<HashMap<String, Option<HashSet<String>>>>,
+}
+
+impl<'a> TypeDeclarations<'a> {
+    pub(crate) fn new(collection: &'a Collection) -> Self {
+        let mut files = Vec::new();
+        let types_dir = collection.root.join(&collection.settings.types_folder);
+        if types_dir.exists() {
+            collect_type_files(&types_dir, &mut files);
+        }
+        let mut paths = HashMap::new();
+        for path in files {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                paths.entry(stem.to_lowercase()).or_insert(path);
+            }
+        }
+        Self {
+            collection,
+            paths,
+            declared: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn collection(&self) -> &'a Collection {
+        self.collection
+    }
+
+    /// The definition file of `type_name`.
+    pub(crate) fn path(&self, type_name: &str) -> Option<&Path> {
+        self.paths
+            .get(&type_name.to_lowercase())
+            .map(PathBuf::as_path)
+    }
+
+    /// Whether the type's own definition file declares `field_name`. Falls
+    /// back to the loaded fields when the file cannot be read.
+    pub(crate) fn declares(&self, type_name: &str, field_name: &str) -> bool {
+        let Some(type_def) = self.collection.types.get(type_name) else {
+            return false;
+        };
+        if !type_def.fields.contains_key(field_name) {
+            return false;
+        }
+        let mut declared = self.declared.borrow_mut();
+        let names = declared.entry(type_name.to_string()).or_insert_with(|| {
+            let text = std::fs::read_to_string(self.path(type_name)?).ok()?;
+            Some(
+                crate::text::type_field_declarations(&text)
+                    .into_iter()
+                    .map(|decl| decl.name)
+                    .collect(),
+            )
+        });
+        names
+            .as_ref()
+            .is_none_or(|names| names.contains(field_name))
+    }
+}
+
+/// Walk the extends chain from `type_name` and return the first type that
+/// declares `field_name` itself.
+pub(crate) fn find_declaring_type(
+    collection: &Collection,
+    type_name: &str,
+    field_name: &str,
+) -> Option<String> {
+    let declarations = TypeDeclarations::new(collection);
+    let mut visited = HashSet::new();
+    let mut current = Some(type_name.to_lowercase());
+    while let Some(name) = current {
+        if !visited.insert(name.clone()) {
+            break;
+        }
+        let type_def = collection.types.get(&name)?;
+        if declarations.declares(&name, field_name) {
+            return Some(name);
+        }
+        current = type_def.extends.as_ref().map(|p| p.to_lowercase());
+    }
+    None
+}
+
+/// Sub-fields of an object field, or of the objects in a list field.
+pub(crate) fn child_field_defs(def: &FieldDef) -> Option<&HashMap<String, FieldDef>> {
+    def.fields
+        .as_ref()
+        .or_else(|| def.items.as_ref().and_then(|items| items.fields.as_ref()))
+}
+
+/// Look up the definition at a field path such as `["address", "city"]`,
+/// descending through object sub-fields and list `items`. The first segment
+/// is looked up in `type_names` (or every type when empty).
+pub(crate) fn field_def_for_path(
+    collection: &Collection,
+    type_names: &[String],
+    path: &[String],
+) -> Option<FieldDef> {
+    let (first, rest) = path.split_first()?;
+    let mut def = if type_names.is_empty() {
+        collection
+            .types
+            .values()
+            .find_map(|t| t.fields.get(first))?
+    } else {
+        type_names
+            .iter()
+            .filter_map(|name| collection.types.get(name))
+            .find_map(|t| t.fields.get(first))?
+    };
+    for segment in rest {
+        def = child_field_defs(def)?.get(segment)?;
+    }
+    Some(def.clone())
+}
+
 fn scan_dir_recursive(collection: &Collection, dir: &Path, files: &mut Vec<PathBuf>) {
     let entries = match std::fs::read_dir(dir) {
         Ok(entries) => entries,
@@ -176,7 +375,7 @@ fn match_glob_pattern(pattern: &str, path: &str) -> bool {
 // ---------------------------------------------------------------------------
 
 /// Normalize path segments by resolving `.` and `..`.
-fn normalize_path_segments(path: &str) -> String {
+pub(crate) fn normalize_path_segments(path: &str) -> String {
     let mut parts: Vec<&str> = Vec::new();
     for segment in path.split('/') {
         match segment {
@@ -198,32 +397,36 @@ fn normalize_path_segments(path: &str) -> String {
     }
 }
 
+/// Type choices for a new note at `rel_path`: types whose match rules fit the
+/// path first, the rest alphabetically, then `None` for an untyped note.
+pub(crate) fn new_note_types(collection: &Collection, rel_path: &str) -> Vec<Option<String>> {
+    let matched = collection.determine_types_for_path(&serde_json::json!({}), Some(rel_path));
+    let mut names: Vec<String> = collection.types.keys().cloned().collect();
+    names.sort_by_key(|n| {
+        (
+            !matched.iter().any(|m| m.eq_ignore_ascii_case(n)),
+            n.clone(),
+        )
+    });
+    names
+        .into_iter()
+        .map(Some)
+        .chain(std::iter::once(None))
+        .collect()
+}
+
 /// Resolve a link target string to an absolute path within the collection.
 ///
 /// Handles relative paths (`./`, `../`), root-relative (`/foo`), exact matches,
 /// extension inference (`.md` + configured extensions), and case-insensitive
-/// stem matching for simple names.
+/// stem matching for simple names, ordered by `tie_break` when ambiguous.
 pub(crate) fn resolve_link_target(
     collection: &Collection,
     target: &str,
     source_rel_path: Option<&str>,
+    tie_break: &[LinkTieBreak],
 ) -> Option<PathBuf> {
-    // Strip wikilink syntax if accidentally passed through
-    let target = if target.starts_with("[[") && target.ends_with("]]") {
-        let inner = &target[2..target.len() - 2];
-        inner
-            .split('|')
-            .next()
-            .unwrap_or(inner)
-            .split('#')
-            .next()
-            .unwrap_or(inner)
-            .trim()
-    } else {
-        target.split('#').next().unwrap_or(target).trim()
-    };
-
-    if target.is_empty() {
+    if target.trim().is_empty() {
         debug!("resolve_link_target: empty target");
         return None;
     }
@@ -239,120 +442,280 @@ pub(crate) fn resolve_link_target(
         })
         .collect();
 
-    // Resolve relative/root-relative targets to a normalized relative path
-    let resolved = if target.starts_with("./") || target.starts_with("../") {
-        let source_dir = source_rel_path
-            .and_then(|s| Path::new(s).parent())
-            .unwrap_or(Path::new(""));
-        let joined = source_dir.join(target);
-        normalize_path_segments(&joined.to_string_lossy().replace('\\', "/"))
-    } else if target.starts_with('/') {
-        target[1..].to_string()
-    } else {
-        target.to_string()
+    let resolver = PathResolver::new(
+        known.iter().map(String::as_str),
+        &collection.settings.extensions,
+        tie_break,
+    );
+    let Some(rel) = resolver.resolve(target, source_rel_path) else {
+        debug!("resolve_link_target: no match found");
+        return None;
     };
+    debug!(matched = %rel, "resolve_link_target: matched");
+    Some(collection.root.join(rel))
+}
 
-    debug!(resolved = %resolved, "resolve_link_target: normalized target");
-
-    // 1. Exact match
-    if known.contains(&resolved) {
-        debug!("resolve_link_target: exact match");
-        return Some(collection.root.join(&resolved));
-    }
+/// Link resolution over a fixed list of collection-relative paths — the path,
+/// extension and stem steps of `resolve_link_target`, shared with the
+/// in-memory resolver over the file index.
+pub(crate) struct PathResolver {
+    paths: HashSet<String>,
+    stems: HashMap<String, Vec<String>>,
+    extensions: Vec<String>,
+    tie_break: Vec<LinkTieBreak>,
+}
 
-    // 2. Extension inference — try .md, then configured extensions
-    if !resolved.contains('.')
-        || (!resolved.ends_with(".md") && !has_known_extension(collection, &resolved))
-    {
-        let with_md = format!("{}.md", resolved);
-        if known.contains(&with_md) {
-            debug!("resolve_link_target: matched with .md extension");
-            return Some(collection.root.join(&with_md));
-        }
-        for ext in &collection.settings.extensions {
-            let with_ext = format!("{}.{}", resolved, ext);
-            if known.contains(&with_ext) {
-                debug!(ext = %ext, "resolve_link_target: matched with configured extension");
-                return Some(collection.root.join(&with_ext));
+impl PathResolver {
+    pub(crate) fn new(
+        paths: impl IntoIterator<Item = impl Into<String>>,
+        extensions: &[String],
+        tie_break: &[LinkTieBreak],
+    ) -> Self {
+        let paths: HashSet<String> = paths.into_iter().map(Into::into).collect();
+        let mut stems: HashMap<String, Vec<String>> = HashMap::new();
+        for rel in &paths {
+            if let Some(stem) = Path::new(rel).file_stem().and_then(|s| s.to_str()) {
+                stems
+                    .entry(stem.to_lowercase())
+                    .or_default()
+                    .push(rel.clone());
             }
         }
+        Self {
+            paths,
+            stems,
+            extensions: extensions.to_vec(),
+            tie_break: tie_break.to_vec(),
+        }
     }
 
-    // 3. Stem match — case-insensitive filename stem for simple names (no path separator)
-    if !resolved.contains('/') {
-        let target_lower = resolved.to_lowercase();
-        for rel in &known {
-            let stem = Path::new(rel)
-                .file_stem()
-                .and_then(|s| s.to_str())
+    /// The path `target` points at, trying in order: the normalized path
+    /// itself, the path with `.md` or a configured extension appended, and —
+    /// for names without `/` — a case-insensitive stem match, ordered by the
+    /// tie-break criteria when several notes share the stem.
+    pub(crate) fn resolve(&self, target: &str, source_rel_path: Option<&str>) -> Option<&str> {
+        // Strip wikilink syntax if accidentally passed through
+        let target = if target.starts_with("[[") && target.ends_with("]]") {
+            let inner = &target[2..target.len() - 2];
+            inner.split('|').next().unwrap_or(inner)
+        } else {
+            target
+        };
+        let target = target.split('#').next().unwrap_or(target).trim();
+        if target.is_empty() {
+            return None;
+        }
+
+        // Resolve relative/root-relative targets to a normalized relative path
+        let resolved = if target.starts_with("./") || target.starts_with("../") {
+            let source_dir = source_rel_path
+                .and_then(|s| s.rsplit_once('/'))
+                .map(|(dir, _)| dir)
                 .unwrap_or("");
-            if stem == resolved || stem.to_lowercase() == target_lower {
-                debug!(matched = %rel, "resolve_link_target: stem match");
-                return Some(collection.root.join(rel));
+            normalize_path_segments(&format!("{}/{}", source_dir, target))
+        } else {
+            target.trim_start_matches('/').to_string()
+        };
+
+        // 1. Exact match
+        if let Some(rel) = self.paths.get(resolved.as_str()) {
+            return Some(rel.as_str());
+        }
+
+        // 2. Extension inference — try .md, then configured extensions
+        if !resolved.contains('.')
+            || (!resolved.ends_with(".md") && !self.has_known_extension(&resolved))
+        {
+            let candidates =
+                std::iter::once("md").chain(self.extensions.iter().map(String::as_str));
+            for ext in candidates {
+                if let Some(rel) = self.paths.get(format!("{}.{}", resolved, ext).as_str()) {
+                    return Some(rel.as_str());
+                }
             }
         }
+
+        // 3. Stem match — case-insensitive filename stem for simple names (no path separator).
+        // Several matches are ordered by `tie_break`; the first wins.
+        if resolved.contains('/') {
+            return None;
+        }
+        // A name written with its extension (`plan.md`) matches files of that
+        // name only.
+        let name = resolved.to_lowercase();
+        let candidates: Vec<String> = match self.stems.get(&name) {
+            Some(candidates) => candidates.clone(),
+            None if self.has_known_extension(&name) => {
+                let (stem, _) = name.rsplit_once('.')?;
+                self.stems
+                    .get(stem)?
+                    .iter()
+                    .filter(|rel| {
+                        rel.rsplit('/').next().map(str::to_lowercase).as_deref() == Some(&name)
+                    })
+                    .cloned()
+                    .collect()
+            }
+            None => return None,
+        };
+        let ordered = order_stem_matches(candidates, source_rel_path, &self.tie_break);
+        let first = ordered.first()?;
+        self.paths.get(first.as_str()).map(String::as_str)
     }
 
-    debug!("resolve_link_target: no match found");
-    None
+    fn has_known_extension(&self, path: &str) -> bool {
+        path.ends_with(".md")
+            || self
+                .extensions
+                .iter()
+                .any(|ext| path.ends_with(&format!(".{}", ext)))
+    }
 }
 
-fn has_known_extension(collection: &Collection, path: &str) -> bool {
-    if path.ends_with(".md") {
-        return true;
-    }
-    for ext in &collection.settings.extensions {
-        if path.ends_with(&format!(".{}", ext)) {
-            return true;
+/// Paths whose file stem equals `name`, ignoring case.
+pub(crate) fn stem_matches<'a>(paths: impl Iterator<Item = &'a str>, name: &str) -> Vec<String> {
+    let name = name.to_lowercase();
+    paths
+        .filter(|rel| {
+            Path::new(rel)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|stem| stem.to_lowercase() == name)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Order stem matches by the configured criteria, then alphabetically, so
+/// ambiguous links always resolve the same way.
+pub(crate) fn order_stem_matches(
+    mut candidates: Vec<String>,
+    source_rel_path: Option<&str>,
+    tie_break: &[LinkTieBreak],
+) -> Vec<String> {
+    let source_dir = source_rel_path
+        .and_then(|s| s.rsplit_once('/'))
+        .map(|(dir, _)| dir)
+        .unwrap_or("");
+    let dir_of = |rel: &str| {
+        rel.rsplit_once('/')
+            .map(|(dir, _)| dir)
+            .unwrap_or("")
+            .to_string()
+    };
+    candidates.sort_by(|a, b| {
+        let mut ordering = std::cmp::Ordering::Equal;
+        for criterion in tie_break {
+            ordering = ordering.then_with(|| match criterion {
+                LinkTieBreak::SameFolder => {
+                    (dir_of(b) == source_dir).cmp(&(dir_of(a) == source_dir))
+                }
+                LinkTieBreak::ShortestPath => a.len().cmp(&b.len()),
+            });
         }
-    }
-    false
+        ordering.then_with(|| a.cmp(b))
+    });
+    candidates
 }
 
 /// Parse a frontmatter link value and extract the target string.
 ///
 /// Handles: `[[target]]`, `[[target|alias]]`, `[text](path)`, bare paths.
 pub(crate) fn parse_link_value(value: &str) -> Option<String> {
-    let value = value.trim();
-    if value.is_empty() {
+    link_value_target_span(value).map(|(start, end)| value[start..end].to_string())
+}
+
+/// Byte range of the target inside a frontmatter link value, as parsed by
+/// `parse_link_value` — the part a retarget replaces, leaving any alias,
+/// anchor and brackets in place.
+pub(crate) fn link_value_target_span(value: &str) -> Option<(usize, usize)> {
+    let lead = value.len() - value.trim_start().len();
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
         return None;
     }
+    // The trimmed span of `part`, which starts at byte `offset` of `value`.
+    let span = |offset: usize, part: &str| {
+        let part_lead = part.len() - part.trim_start().len();
+        let len = part.trim().len();
+        (len > 0).then_some((offset + part_lead, offset + part_lead + len))
+    };
 
     // Wikilink: [[target]] or [[target|alias]]
-    if value.starts_with("[[") && value.ends_with("]]") {
-        let inner = &value[2..value.len() - 2];
+    if trimmed.starts_with("[[") && trimmed.ends_with("]]") {
+        let inner = &trimmed[2..trimmed.len() - 2];
         let target = inner.split('|').next().unwrap_or(inner);
-        let target = target.split('#').next().unwrap_or(target).trim();
-        if target.is_empty() {
-            return None;
-        }
-        return Some(target.to_string());
+        let target = target.split('#').next().unwrap_or(target);
+        return span(lead + 2, target);
     }
 
     // Markdown link: [text](path)
-    if value.starts_with('[') {
-        if let Some(bracket_end) = value.find("](") {
-            if value.ends_with(')') {
-                let path = &value[bracket_end + 2..value.len() - 1];
-                let path = path.trim();
-                if !path.is_empty() && !path.starts_with("http://") && !path.starts_with("https://")
-                {
-                    let target = path.split('#').next().unwrap_or(path).to_string();
-                    if !target.is_empty() {
-                        return Some(target);
-                    }
+    if trimmed.starts_with('[') {
+        if let Some(bracket_end) = trimmed.find("](") {
+            if trimmed.ends_with(')') {
+                let path = &trimmed[bracket_end + 2..trimmed.len() - 1];
+                let offset = lead + bracket_end + 2;
+                let inner = path.trim();
+                if inner.starts_with("http://") || inner.starts_with("https://") {
+                    return None;
                 }
-                return None;
+                let target = path.split('#').next().unwrap_or(path);
+                return span(offset, target);
             }
         }
     }
 
     // Bare path — skip external URLs
-    if value.starts_with("http://") || value.starts_with("https://") {
+    if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
         return None;
     }
 
-    Some(value.to_string())
+    Some((lead, lead + trimmed.len()))
+}
+
+/// Compute a relative path from `source` to `target`, where both are
+/// collection-relative paths (e.g. `notes/foo.md`, `other/bar.md`). The result
+/// always starts with `./` or `../`.
+pub(crate) fn relative_path_from(source: &str, target: &str) -> String {
+    let src_dir = match source.rfind('/') {
+        Some(i) => &source[..i],
+        None => "",
+    };
+    let tgt_parts: Vec<&str> = target.split('/').collect();
+    let src_parts: Vec<&str> = if src_dir.is_empty() {
+        Vec::new()
+    } else {
+        src_dir.split('/').collect()
+    };
+
+    // Find the common prefix length
+    let common = src_parts
+        .iter()
+        .zip(tgt_parts.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let ups = src_parts.len() - common;
+    let mut parts: Vec<&str> = Vec::new();
+    for _ in 0..ups {
+        parts.push("..");
+    }
+    for segment in &tgt_parts[common..] {
+        parts.push(segment);
+    }
+
+    let path = if parts.is_empty() {
+        tgt_parts.last().unwrap_or(&"").to_string()
+    } else {
+        parts.join("/")
+    };
+    // Without `./`, a path into the same folder or below reads as
+    // collection-relative (or as a bare stem) when the link is resolved.
+    if path.starts_with("../") {
+        path
+    } else {
+        format!("./{}", path)
+    }
 }
 
 pub(crate) fn rel_path_from_uri(collection: &Collection, uri: &Url) -> Option<String> {
@@ -365,3 +728,31 @@ pub(crate) fn rel_path_from_uri(collection: &Collection, uri: &Url) -> Option<St
 pub(crate) fn uri_from_rel_path(collection: &Collection, rel_path: &str) -> Option<Url> {
     Url::from_file_path(collection.root.join(rel_path)).ok()
 }
+
+/// Wrap plain text edits for one file as a `documentChanges` operation.
+pub(crate) fn text_document_edit(uri: Url, edits: Vec<TextEdit>) -> DocumentChangeOperation {
+    DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+        edits: edits.into_iter().map(OneOf::Left).collect(),
+    })
+}
+
+/// Like `text_document_edit`, with every edit tagged with `annotation_id`.
+pub(crate) fn annotated_text_document_edit(
+    uri: Url,
+    edits: Vec<TextEdit>,
+    annotation_id: &str,
+) -> DocumentChangeOperation {
+    DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+        edits: edits
+            .into_iter()
+            .map(|text_edit| {
+                OneOf::Right(AnnotatedTextEdit {
+                    text_edit,
+                    annotation_id: annotation_id.to_string(),
+                })
+            })
+            .collect(),
+    })
+}
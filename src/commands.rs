@@ -1,4 +1,4 @@
-use mdbase::types::schema::GeneratedStrategy;
+use mdbase::types::schema::{FieldDef, GeneratedStrategy, TypeDef};
 use mdbase::Collection;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
@@ -18,6 +18,8 @@ pub async fn execute(
         "mdbase.typeInfo" => type_info(state, args).await,
         "mdbase.validateCollection" => validate_collection(client, state).await,
         "mdbase.queryCollection" => query_collection(state, args).await,
+        "mdbase.splitNote" => split_note(client, state, args).await,
+        "mdbase.mergeNotes" => merge_notes(client, state, args).await,
         _ => {
             client
                 .log_message(
@@ -59,10 +61,7 @@ async fn type_info(
 
     let mut prompt_fields = Vec::new();
     for (field_name, field_def) in &type_def.fields {
-        if field_def.required
-            && field_def.default.is_none()
-            && !has_generated_in_chain(&collection, &type_name, field_name)
-        {
+        if is_prompt_field(&collection, &type_name, field_name, field_def) {
             let mut info = serde_json::json!({
                 "name": field_name,
                 "type": field_def.field_type,
@@ -103,6 +102,19 @@ async fn create_file(
         .get(0)
         .cloned()
         .unwrap_or_else(|| serde_json::json!({}));
+    // The link this note was created from, if any (see `link_fixes`).
+    let source_link = input.as_object_mut().and_then(|o| o.remove("link"));
+    // Types for the user to pick from when the caller left the choice open.
+    if let Some(choices) = input.as_object_mut().and_then(|o| o.remove("typeChoices")) {
+        let Some(type_name) = pick_type(client, &collection, &choices).await else {
+            return Ok(None);
+        };
+        let Some(typed) = crate::link_fixes::typed_note_input(&collection, input, &type_name)
+        else {
+            return Ok(None);
+        };
+        input = typed;
+    }
 
     if let Some(type_name) = input.get("type").and_then(|v| v.as_str()) {
         let tn_lower = type_name.to_lowercase();
@@ -113,29 +125,7 @@ async fn create_file(
                 .cloned()
                 .unwrap_or_else(|| serde_json::json!({}));
             let mut fm_obj = fm.as_object().cloned().unwrap_or_default();
-
-            // Pre-generate values for fields with generated strategies.
-            // Walk the extends chain so that overridden fields (e.g. person
-            // redefining zettelid without `generated`) still pick up the
-            // ancestor's strategy.
-            for (field_name, _) in &type_def.fields {
-                if fm_obj.contains_key(field_name) {
-                    continue;
-                }
-                if let Some(value) = generate_field_value(&collection, &tn_lower, field_name) {
-                    fm_obj.insert(field_name.clone(), value);
-                }
-            }
-
-            // Pre-apply defaults so they appear in the written file
-            // (collection.create() only uses defaults for validation).
-            for (field_name, field_def) in &type_def.fields {
-                if !fm_obj.contains_key(field_name) {
-                    if let Some(default) = &field_def.default {
-                        fm_obj.insert(field_name.clone(), default.clone());
-                    }
-                }
-            }
+            prefill_frontmatter(&collection, &tn_lower, type_def, &mut fm_obj);
 
             // Derive path from filename_pattern if none provided.
             let has_path = input
@@ -171,6 +161,10 @@ async fn create_file(
             strip_frontmatter_field(&full_path, "type");
         }
 
+        if let Some(link) = &source_link {
+            relink_source(client, state, &collection, link, path).await;
+        }
+
         if let Ok(uri) = Url::from_file_path(full_path) {
             let _ = client
                 .show_document(ShowDocumentParams {
@@ -185,6 +179,147 @@ async fn create_file(
     Ok(Some(result))
 }
 
+/// Ask the user to pick one of the type names in `choices`.
+async fn pick_type(
+    client: &Client,
+    collection: &Collection,
+    choices: &serde_json::Value,
+) -> Option<String> {
+    let names: Vec<&str> = choices
+        .as_array()?
+        .iter()
+        .filter_map(|v| v.as_str())
+        .filter(|name| collection.types.contains_key(*name))
+        .collect();
+    let items = names
+        .iter()
+        .map(|name| MessageActionItem {
+            title: collection.types[*name].name.clone(),
+            properties: Default::default(),
+        })
+        .collect();
+    let picked = client
+        .show_message_request(MessageType::INFO, "Create note as", Some(items))
+        .await
+        .ok()??;
+    names
+        .into_iter()
+        .find(|name| collection.types[*name].name == picked.title)
+        .map(str::to_string)
+}
+
+/// Point the link a note was created from at the created path, when the
+/// type's filename pattern put it somewhere the link doesn't resolve to.
+async fn relink_source(
+    client: &Client,
+    state: &BackendState,
+    collection: &Collection,
+    link: &serde_json::Value,
+    created_path: &str,
+) {
+    let Some(uri) = link
+        .get("uri")
+        .and_then(|v| v.as_str())
+        .and_then(|u| Url::parse(u).ok())
+    else {
+        return;
+    };
+    let Some(range) = link
+        .get("range")
+        .and_then(|r| serde_json::from_value::<Range>(r.clone()).ok())
+    else {
+        return;
+    };
+    let Some(source_rel) = crate::collection_utils::rel_path_from_uri(collection, &uri) else {
+        return;
+    };
+    let Some(text) = state.document_text(&uri) else {
+        return;
+    };
+    let Some(body_link) = crate::body_links::body_link_at(
+        &text,
+        range.start.line as usize,
+        range.start.character as usize,
+    ) else {
+        return;
+    };
+
+    let created = collection.root.join(created_path);
+    let resolved = crate::link_resolve::resolve_target(
+        state,
+        collection,
+        &body_link.target,
+        Some(&source_rel),
+    );
+    if resolved.as_deref() == Some(created.as_path()) {
+        return;
+    }
+
+    let paths: Vec<String> = state
+        .file_index
+        .with_entries(|entries| entries.iter().map(|e| e.rel_path.clone()).collect());
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+    let new_text = crate::link_style::rewrite_link(
+        &state.config(),
+        &body_link,
+        &source_rel,
+        created_path,
+        &paths,
+    );
+    let mut changes = std::collections::HashMap::new();
+    changes.insert(uri, vec![TextEdit { range, new_text }]);
+    let _ = client
+        .apply_edit(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        })
+        .await;
+}
+
+/// A field the user has to fill in: required, with no default value and no
+/// generated strategy anywhere in the extends chain.
+pub(crate) fn is_prompt_field(
+    collection: &Collection,
+    type_name: &str,
+    field_name: &str,
+    field_def: &FieldDef,
+) -> bool {
+    field_def.required
+        && field_def.default.is_none()
+        && !has_generated_in_chain(collection, type_name, field_name)
+}
+
+/// Fill in generated values and defaults for fields not already present.
+pub(crate) fn prefill_frontmatter(
+    collection: &Collection,
+    type_name: &str,
+    type_def: &TypeDef,
+    fm_obj: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    // Pre-generate values for fields with generated strategies.
+    // Walk the extends chain so that overridden fields (e.g. person
+    // redefining zettelid without `generated`) still pick up the
+    // ancestor's strategy.
+    for field_name in type_def.fields.keys() {
+        if fm_obj.contains_key(field_name) {
+            continue;
+        }
+        if let Some(value) = generate_field_value(collection, type_name, field_name) {
+            fm_obj.insert(field_name.clone(), value);
+        }
+    }
+
+    // Pre-apply defaults so they appear in the written file
+    // (collection.create() only uses defaults for validation).
+    for (field_name, field_def) in &type_def.fields {
+        if !fm_obj.contains_key(field_name) {
+            if let Some(default) = &field_def.default {
+                fm_obj.insert(field_name.clone(), default.clone());
+            }
+        }
+    }
+}
+
 /// Check whether a field has a generated strategy anywhere in the extends chain.
 fn has_generated_in_chain(collection: &Collection, type_name: &str, field_name: &str) -> bool {
     let mut current = Some(type_name.to_string());
@@ -204,7 +339,7 @@ fn has_generated_in_chain(collection: &Collection, type_name: &str, field_name:
 }
 
 /// Walk the extends chain to find the first `filename_pattern`.
-fn find_filename_pattern(collection: &Collection, type_name: &str) -> Option<String> {
+pub(crate) fn find_filename_pattern(collection: &Collection, type_name: &str) -> Option<String> {
     let mut current = Some(type_name.to_string());
     while let Some(name) = current {
         let type_def = collection.types.get(&name)?;
@@ -251,7 +386,7 @@ fn generate_field_value(
 }
 
 /// Substitute `{field}` placeholders in a filename pattern.
-fn derive_path_from_pattern(
+pub(crate) fn derive_path_from_pattern(
     pattern: &str,
     fm: &serde_json::Map<String, serde_json::Value>,
 ) -> Option<String> {
@@ -330,3 +465,134 @@ async fn query_collection(
         .unwrap_or_default();
     Ok(Some(crate::symbols::query_collection(state, query)))
 }
+
+/// Split a note at every heading of a level into separate notes.
+///
+/// Arguments: `{ uri, level = 2, inherit = [], embed = false }`. Applies the
+/// edit through the client and returns the created paths.
+async fn split_note(
+    client: &Client,
+    state: &BackendState,
+    args: &[serde_json::Value],
+) -> Result<Option<serde_json::Value>> {
+    let Some(collection) = state.get_collection() else {
+        client
+            .log_message(MessageType::ERROR, "mdbase collection not loaded")
+            .await;
+        return Ok(None);
+    };
+    let input = args.first().cloned().unwrap_or_default();
+    let Some(uri) = input
+        .get("uri")
+        .and_then(|v| v.as_str())
+        .and_then(|u| Url::parse(u).ok())
+    else {
+        return Ok(None);
+    };
+    let options = crate::split_note::SplitOptions {
+        level: input
+            .get("level")
+            .and_then(|v| v.as_u64())
+            .map_or(2, |l| l.clamp(1, 6) as usize),
+        inherit: input
+            .get("inherit")
+            .and_then(|v| v.as_array())
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|f| f.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        embed: input
+            .get("embed")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
+    let Some((edit, created)) = crate::split_note::split_edit(state, &collection, &uri, &options)
+    else {
+        client
+            .show_message(
+                MessageType::INFO,
+                format!("No level {} headings to split at", options.level),
+            )
+            .await;
+        return Ok(None);
+    };
+    let applied = client
+        .apply_edit(edit)
+        .await
+        .map(|response| response.applied)
+        .unwrap_or(false);
+    Ok(Some(serde_json::json!({
+        "applied": applied,
+        "created": created,
+    })))
+}
+
+/// Merge a source note into a destination note.
+///
+/// Arguments: `{ source, destination }`, each a URI or collection-relative
+/// path. Applies the edit through the client, which asks for confirmation,
+/// and returns the conflicting fields.
+async fn merge_notes(
+    client: &Client,
+    state: &BackendState,
+    args: &[serde_json::Value],
+) -> Result<Option<serde_json::Value>> {
+    let Some(collection) = state.get_collection() else {
+        client
+            .log_message(MessageType::ERROR, "mdbase collection not loaded")
+            .await;
+        return Ok(None);
+    };
+    let input = args.first().cloned().unwrap_or_default();
+    let note_uri = |key: &str| {
+        let value = input.get(key).and_then(|v| v.as_str())?;
+        match Url::parse(value) {
+            Ok(uri) if uri.scheme() == "file" => Some(uri),
+            _ => crate::collection_utils::uri_from_rel_path(&collection, value),
+        }
+    };
+    let (Some(source), Some(destination)) = (note_uri("source"), note_uri("destination")) else {
+        return Ok(None);
+    };
+
+    let Some((edit, conflicts)) =
+        crate::merge_notes::merge_edit(state, &collection, &source, &destination)
+    else {
+        client
+            .show_message(MessageType::ERROR, "mdbase: cannot merge these notes")
+            .await;
+        return Ok(None);
+    };
+    let applied = client
+        .apply_edit(edit)
+        .await
+        .map(|response| response.applied)
+        .unwrap_or(false);
+    if applied && !conflicts.is_empty() {
+        let fields: Vec<&str> = conflicts.iter().map(|c| c.field.as_str()).collect();
+        client
+            .show_message(
+                MessageType::WARNING,
+                format!("Kept destination values for: {}", fields.join(", ")),
+            )
+            .await;
+    }
+    let conflicts: Vec<serde_json::Value> = conflicts
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "field": c.field,
+                "destination": c.destination,
+                "source": c.source,
+            })
+        })
+        .collect();
+    Ok(Some(serde_json::json!({
+        "applied": applied,
+        "conflicts": conflicts,
+    })))
+}
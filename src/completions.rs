@@ -1,6 +1,11 @@
 use tower_lsp::lsp_types::*;
 use tracing::{debug, warn};
 
+use crate::body_links::LinkFormat;
+use crate::file_index::FileEntry;
+use crate::link_rank;
+use crate::link_resolve;
+use crate::link_style;
 use crate::state::BackendState;
 use crate::text;
 
@@ -26,6 +31,10 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Co
     let line_text = text.lines().nth(line_idx).unwrap_or("").to_string();
     let column = position.character as usize;
 
+    if let Some(items) = crate::skeleton::completions(&collection, uri, &text, position) {
+        return Some(CompletionResponse::Array(items));
+    }
+
     // Check for link completion context first — works in both body and frontmatter
     if let Some(ctx) = text::link_completion_context(&line_text, column) {
         let rel_path = uri.to_file_path().ok().and_then(|p| {
@@ -33,13 +42,14 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Co
                 .ok()
                 .map(|r| r.to_string_lossy().to_string().replace('\\', "/"))
         });
-        return Some(CompletionResponse::Array(provide_link_completions(
+        return Some(provide_link_completions(
             state,
             &ctx,
+            &text,
             line_idx,
             column,
             rel_path.as_deref(),
-        )));
+        ));
     }
 
     let in_frontmatter = text::is_in_frontmatter(&text, line_idx);
@@ -47,9 +57,24 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Co
         debug!(uri = %uri, line = line_idx, "completion: not in frontmatter");
     }
     if in_frontmatter {
+        if let Some(items) =
+            frontmatter_tag_completions(state, &collection, uri, &text, line_idx, column)
+        {
+            return Some(CompletionResponse::Array(items));
+        }
+        if let Some(items) = type_name_completions(&collection, &text, line_idx, column) {
+            return Some(CompletionResponse::Array(items));
+        }
+
         let colon_idx = line_text.find(':');
         let is_field_name_pos = colon_idx.is_none() || column <= colon_idx.unwrap_or(0);
 
+        if is_field_name_pos {
+            if let Some(items) = nested_field_completions(&collection, uri, &text, line_idx) {
+                return Some(CompletionResponse::Array(items));
+            }
+        }
+
         let parsed = state
             .documents
             .get(uri)
@@ -127,7 +152,12 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Co
 
         if let Some(field_name) = text::field_name_from_line(&line_text) {
             debug!(uri = %uri, field_name = %field_name, "completion: looking up field def for value completion");
-            if let Some(field_def) = field_def_for_types(&collection, &type_names, &field_name) {
+            let path = text::field_path_at(&text, line_idx)
+                .map(|p| p.segments())
+                .unwrap_or_else(|| vec![field_name.clone()]);
+            let field_def =
+                crate::collection_utils::field_def_for_path(&collection, &type_names, &path);
+            if let Some(field_def) = &field_def {
                 debug!(uri = %uri, field_name = %field_name, field_type = %field_def.field_type, has_values = field_def.values.is_some(), "completion: found field def");
                 if let Some(values) = &field_def.values {
                     let items = values
@@ -141,6 +171,19 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Co
                         .collect();
                     return Some(CompletionResponse::Array(items));
                 }
+                if crate::dates::is_date_type(&field_def.field_type) {
+                    let colon_idx = line_text.find(':').unwrap_or(0);
+                    let after = &line_text[colon_idx + 1..];
+                    let value_start = (colon_idx + 1 + after.len() - after.trim_start().len())
+                        .min(column.max(colon_idx + 1));
+                    let typed = line_text.get(value_start..column).unwrap_or("");
+                    let range = Range::new(
+                        Position::new(line_idx as u32, value_start as u32),
+                        Position::new(line_idx as u32, column as u32),
+                    );
+                    let items = crate::dates::completion_items(&field_def.field_type, typed, range);
+                    return Some(CompletionResponse::Array(items));
+                }
                 if field_def.field_type == "boolean" {
                     let items = vec![
                         CompletionItem::new_simple("true".to_string(), "boolean".to_string()),
@@ -148,14 +191,21 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Co
                     ];
                     return Some(CompletionResponse::Array(items));
                 }
-                if is_link_field(&field_def) {
-                    let target_type = link_target_type(&field_def);
+                if is_link_field(field_def) {
+                    let target_type = link_target_type(field_def);
                     let items = link_target_completions(state, target_type.as_deref());
                     return Some(CompletionResponse::Array(items));
                 }
             } else {
                 debug!(uri = %uri, field_name = %field_name, "completion: no field def found");
             }
+            let free_text = field_def.as_ref().is_none_or(|d| d.field_type == "string");
+            if free_text && !line_text.starts_with(char::is_whitespace) {
+                let items = existing_value_completions(state, &field_name, &type_names);
+                if !items.is_empty() {
+                    return Some(CompletionResponse::Array(items));
+                }
+            }
         } else {
             debug!(uri = %uri, line_text = %line_text, "completion: could not extract field name from line");
         }
@@ -165,6 +215,12 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Co
             let items = tag_completions(state);
             return Some(CompletionResponse::Array(items));
         }
+        if let Some(partial) = crate::tag_refs::tag_prefix_at(&line_text, column) {
+            if let Some((parent, _)) = partial.rsplit_once('/') {
+                let items = child_tag_completions(state, parent);
+                return Some(CompletionResponse::Array(items));
+            }
+        }
     }
 
     None
@@ -266,7 +322,7 @@ fn is_link_field(def: &FieldDef) -> bool {
     false
 }
 
-fn link_target_type(def: &FieldDef) -> Option<String> {
+pub(crate) fn link_target_type(def: &FieldDef) -> Option<String> {
     if def.field_type == "link" {
         def.target.clone()
     } else if def.field_type == "list" {
@@ -313,107 +369,472 @@ fn tag_completions(state: &BackendState) -> Vec<CompletionItem> {
         .collect()
 }
 
+/// Tag completions for a value of `tags` (or a configured tag field) in the
+/// frontmatter: `tags: [`, `tags: `, or a `- ` item below `tags:`.
+///
+/// Tags already on the note are left out; the rest keep the usage order
+/// from `FileIndex::tag_counts`.
+fn frontmatter_tag_completions(
+    state: &BackendState,
+    collection: &mdbase::Collection,
+    uri: &Url,
+    text: &str,
+    line_idx: usize,
+    column: usize,
+) -> Option<Vec<CompletionItem>> {
+    let field_name = value_field_at(text, line_idx, column)?;
+    let config = state.config();
+    if !config.is_tag_field(&field_name) {
+        return None;
+    }
+
+    if let Some(type_names) = lenient_note_types(collection, uri, text, line_idx) {
+        if let Some(def) = field_def_for_types(collection, &type_names, &field_name) {
+            let list_of_strings = def.field_type == "list"
+                && def
+                    .items
+                    .as_ref()
+                    .is_none_or(|items| items.field_type == "string");
+            if def.values.is_some() || (field_name != "tags" && !list_of_strings) {
+                return None;
+            }
+        }
+    }
+
+    let existing: std::collections::HashSet<String> =
+        existing_values(text, &field_name, line_idx, column)
+            .into_iter()
+            .map(|v| v.trim_start_matches('#').to_string())
+            .collect();
+    let items = state
+        .file_index
+        .tag_counts()
+        .into_iter()
+        .filter(|(tag, _)| !existing.contains(tag))
+        .enumerate()
+        .map(|(rank, (tag, count))| CompletionItem {
+            label: tag,
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some(format!("used {} times", count)),
+            sort_text: Some(format!("{:05}", rank)),
+            ..Default::default()
+        })
+        .collect();
+    Some(items)
+}
+
+/// Field name completions inside a nested mapping or a list-of-objects item:
+/// the sub-fields of the enclosing object field, minus keys already set in
+/// the same mapping.
+fn nested_field_completions(
+    collection: &mdbase::Collection,
+    uri: &Url,
+    text: &str,
+    line_idx: usize,
+) -> Option<Vec<CompletionItem>> {
+    let path = text::field_path_at(text, line_idx)?;
+    if path.parents.is_empty() {
+        return None;
+    }
+    let type_names = lenient_note_types(collection, uri, text, line_idx)?;
+    let parent_def =
+        crate::collection_utils::field_def_for_path(collection, &type_names, &path.parents)?;
+    let children = crate::collection_utils::child_field_defs(&parent_def)?;
+
+    // Sibling keys only make sense to exclude for a plain object; list items
+    // each have their own mapping.
+    let mut existing = std::collections::HashSet::new();
+    if parent_def.fields.is_some() {
+        if let Some((start, end)) = text::frontmatter_bounds(text) {
+            for idx in (start..=end).filter(|idx| *idx != line_idx) {
+                if let Some(other) = text::field_path_at(text, idx) {
+                    if other.parents == path.parents {
+                        existing.extend(other.key);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut names: Vec<&String> = children.keys().collect();
+    names.sort();
+    let items = names
+        .into_iter()
+        .filter(|name| !existing.contains(*name))
+        .map(|name| {
+            let def = &children[name];
+            let mut item = CompletionItem::new_simple(name.clone(), field_detail(def));
+            item.kind = Some(CompletionItemKind::FIELD);
+            item.documentation = field_documentation(def);
+            item
+        })
+        .collect();
+    Some(items)
+}
+
+/// The note's types, tolerating a half-typed current line: the line being
+/// edited often leaves the YAML invalid (`tags: [`), so fall back to parsing
+/// without it.
+fn lenient_note_types(
+    collection: &mdbase::Collection,
+    uri: &Url,
+    text: &str,
+    line_idx: usize,
+) -> Option<Vec<String>> {
+    let mut parsed = text::parse_frontmatter(text);
+    if parsed.parse_error || parsed.mapping_error {
+        parsed = text::parse_frontmatter(&remove_line(text, line_idx));
+    }
+    if parsed.parse_error || parsed.mapping_error {
+        return None;
+    }
+    let rel_path = crate::collection_utils::rel_path_from_uri(collection, uri);
+    Some(collection.determine_types_for_path(&parsed.json, rel_path.as_deref()))
+}
+
+/// Type name completions for `type`, `types`, and explicit type keys.
+///
+/// Each item shows the type's description and `extends` parent; types
+/// already listed on the note are left out.
+fn type_name_completions(
+    collection: &mdbase::Collection,
+    text: &str,
+    line_idx: usize,
+    column: usize,
+) -> Option<Vec<CompletionItem>> {
+    let field_name = value_field_at(text, line_idx, column)?;
+    if !crate::collection_utils::is_type_key(collection, &field_name) {
+        return None;
+    }
+    let existing: std::collections::HashSet<String> =
+        existing_values(text, &field_name, line_idx, column)
+            .into_iter()
+            .map(|v| v.to_lowercase())
+            .collect();
+
+    let mut type_names: Vec<&String> = collection.types.keys().collect();
+    type_names.sort();
+    let items = type_names
+        .into_iter()
+        .filter(|name| !existing.contains(*name))
+        .filter_map(|name| collection.types.get(name))
+        .map(|type_def| CompletionItem {
+            label: type_def.name.clone(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some(match &type_def.extends {
+                Some(parent) => format!("type, extends {}", parent),
+                None => "type".to_string(),
+            }),
+            documentation: type_def.description.clone().map(|desc| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: desc,
+                })
+            }),
+            ..Default::default()
+        })
+        .collect();
+    Some(items)
+}
+
+/// The top-level field whose value is being typed: after `key:` on a
+/// top-level line, or in a `- ` list item below `key:`.
+fn value_field_at(text: &str, line_idx: usize, column: usize) -> Option<String> {
+    let line_text = text.lines().nth(line_idx)?;
+    let trimmed = line_text.trim_start();
+    if let Some(item) = trimmed.strip_prefix('-') {
+        let dash_idx = line_text.len() - trimmed.len();
+        if column <= dash_idx || item.contains(':') {
+            return None;
+        }
+        text::field_name_for_position(text, line_idx)
+    } else {
+        let colon_idx = line_text.find(':')?;
+        if column <= colon_idx || line_text.starts_with(char::is_whitespace) {
+            return None;
+        }
+        Some(line_text[..colon_idx].trim().to_string())
+    }
+}
+
+/// Values already set for a top-level list field, excluding the token under
+/// the cursor.
+fn existing_values(text: &str, field_name: &str, line_idx: usize, column: usize) -> Vec<String> {
+    let mut values = Vec::new();
+    let Some((start, end)) = text::frontmatter_bounds(text) else {
+        return values;
+    };
+    for (idx, line) in text.lines().enumerate().take(end + 1).skip(start) {
+        let nested_key =
+            line.starts_with(char::is_whitespace) && !line.trim_start().starts_with('-');
+        if nested_key || text::field_name_for_position(text, idx).as_deref() != Some(field_name) {
+            continue;
+        }
+        for (s, e) in text::value_tokens(line) {
+            let under_cursor = idx == line_idx && column >= s && column <= e;
+            if !under_cursor {
+                values.push(line[s..e].to_string());
+            }
+        }
+    }
+    values
+}
+
+/// Values already used for a free-text field among notes of the same types,
+/// most common first, so spellings stay consistent across the collection.
+fn existing_value_completions(
+    state: &BackendState,
+    field_name: &str,
+    type_names: &[String],
+) -> Vec<CompletionItem> {
+    state
+        .file_index
+        .field_value_counts(field_name, type_names)
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (value, count))| CompletionItem {
+            label: value,
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(format!("used {} times", count)),
+            sort_text: Some(format!("{:05}", rank)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Next-level segments below `parent` for a nested tag (`#parent/…`).
+fn child_tag_completions(state: &BackendState, parent: &str) -> Vec<CompletionItem> {
+    let prefix = format!("{}/", parent);
+    state
+        .file_index
+        .tag_counts()
+        .into_iter()
+        .filter_map(|(tag, count)| {
+            let segment = tag.strip_prefix(&prefix)?;
+            if segment.is_empty() || segment.contains('/') {
+                return None;
+            }
+            Some(CompletionItem {
+                label: segment.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail: Some(format!("{} — used {} times", tag, count)),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Fuzzy-ranked link targets for the text typed so far. The list is capped
+/// and marked incomplete so the client asks again as the query changes.
 fn provide_link_completions(
     state: &BackendState,
     ctx: &text::LinkCompletionContext,
+    text: &str,
     line_idx: usize,
     column: usize,
     source_rel_path: Option<&str>,
-) -> Vec<CompletionItem> {
-    let targets = state.file_index.link_targets_with_display(None);
+) -> CompletionResponse {
     let edit_range = Range {
         start: Position::new(line_idx as u32, ctx.start_col as u32),
         end: Position::new(line_idx as u32, column as u32),
     };
 
-    targets
-        .into_iter()
-        .map(|(rel_path, display_name, preview)| match ctx.kind {
-            text::LinkCompletionKind::Wikilink => {
-                let stem = rel_path.strip_suffix(".md").unwrap_or(&rel_path);
-                let label = display_name.clone().unwrap_or_else(|| stem.to_string());
-                let insert_text = match display_name.as_deref() {
-                    Some(name) if !name.is_empty() && name != stem => {
-                        format!("{}|{}", stem, name)
+    let config = state.config();
+    let format = match ctx.kind {
+        text::LinkCompletionKind::Wikilink => LinkFormat::Wikilink,
+        text::LinkCompletionKind::Markdown => LinkFormat::Markdown,
+    };
+    let extensions = state
+        .get_collection()
+        .map(|c| c.settings.extensions.clone())
+        .unwrap_or_default();
+    let items = state.file_index.with_entries(|entries| {
+        let paths: Vec<&str> = entries.iter().map(|e| e.rel_path.as_str()).collect();
+        let index = link_resolve::IndexResolver::new(entries, &extensions, &config.link_tie_break);
+        let rank_ctx = state.file_index.with_link_graph(entries, &index, |graph| {
+            link_rank::RankContext::build(
+                graph,
+                &index,
+                source_rel_path,
+                text,
+                state.recent_notes(),
+            )
+        });
+        let (ranked, _) = link_rank::rank(
+            entries,
+            &ctx.prefix,
+            &rank_ctx,
+            link_rank::MAX_LINK_COMPLETIONS,
+        );
+        ranked
+            .into_iter()
+            .enumerate()
+            .map(|(idx, target)| {
+                let link_target = link_style::link_target(
+                    &config,
+                    format,
+                    source_rel_path,
+                    &target.entry.rel_path,
+                    &paths,
+                );
+                let alias = target.entry.aliases.iter().find(|a| **a == target.matched);
+                let mut item = match (format, alias) {
+                    (LinkFormat::Wikilink, Some(alias)) => {
+                        alias_completion_item(target.entry, &link_target, alias, edit_range)
                     }
-                    _ => stem.to_string(),
-                };
-                let filter_text = display_name
-                    .as_ref()
-                    .map(|d| format!("{} {} {}", d, stem, rel_path));
-                let documentation = preview.map(|p| {
-                    Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::PlainText,
-                        value: p,
-                    })
-                });
-                CompletionItem {
-                    label,
-                    detail: Some(rel_path.clone()),
-                    kind: Some(CompletionItemKind::FILE),
-                    insert_text: Some(insert_text.clone()),
-                    filter_text,
-                    documentation,
-                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
-                        range: edit_range,
-                        new_text: insert_text,
-                    })),
-                    ..Default::default()
-                }
-            }
-            text::LinkCompletionKind::Markdown => {
-                let label = match source_rel_path {
-                    Some(src) => relative_path_from(src, &rel_path),
-                    None => rel_path.clone(),
+                    _ => link_completion_item(format, target.entry, &link_target, edit_range),
                 };
-                CompletionItem {
-                    label: label.clone(),
-                    detail: Some(rel_path.clone()),
-                    kind: Some(CompletionItemKind::FILE),
-                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
-                        range: edit_range,
-                        new_text: label,
-                    })),
-                    ..Default::default()
+                item.filter_text = Some(target.matched);
+                item.sort_text = Some(format!("{:04}", idx));
+                item
+            })
+            .collect()
+    });
+
+    CompletionResponse::List(CompletionList {
+        is_incomplete: true,
+        items,
+    })
+}
+
+/// A wikilink item for a note matched by one of its aliases, inserting
+/// `target|Alias`.
+fn alias_completion_item(
+    entry: &FileEntry,
+    link_target: &str,
+    alias: &str,
+    edit_range: Range,
+) -> CompletionItem {
+    let insert_text = format!("{}|{}", link_target, alias);
+    CompletionItem {
+        label: alias.to_string(),
+        detail: Some(format!("alias of {}", entry.rel_path)),
+        kind: Some(CompletionItemKind::REFERENCE),
+        insert_text: Some(insert_text.clone()),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range: edit_range,
+            new_text: insert_text,
+        })),
+        data: Some(serde_json::json!({ "relPath": entry.rel_path })),
+        ..Default::default()
+    }
+}
+
+/// A link item inserting `link_target`, the note's path in the configured
+/// link style.
+fn link_completion_item(
+    format: LinkFormat,
+    entry: &FileEntry,
+    link_target: &str,
+    edit_range: Range,
+) -> CompletionItem {
+    let rel_path = &entry.rel_path;
+    match format {
+        LinkFormat::Wikilink => {
+            let label = entry
+                .display_name
+                .clone()
+                .unwrap_or_else(|| link_target.to_string());
+            let insert_text = match entry.display_name.as_deref() {
+                Some(name) if !name.is_empty() && name != link_target => {
+                    format!("{}|{}", link_target, name)
                 }
+                _ => link_target.to_string(),
+            };
+            CompletionItem {
+                label,
+                detail: Some(rel_path.clone()),
+                kind: Some(CompletionItemKind::FILE),
+                insert_text: Some(insert_text.clone()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: edit_range,
+                    new_text: insert_text,
+                })),
+                data: Some(serde_json::json!({ "relPath": rel_path })),
+                ..Default::default()
             }
-        })
-        .collect()
+        }
+        LinkFormat::Markdown => CompletionItem {
+            label: link_target.to_string(),
+            detail: Some(rel_path.clone()),
+            kind: Some(CompletionItemKind::FILE),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range: edit_range,
+                new_text: link_target.to_string(),
+            })),
+            data: Some(serde_json::json!({ "relPath": rel_path })),
+            ..Default::default()
+        },
+    }
 }
 
-/// Compute a relative path from `source` to `target`, where both are
-/// collection-relative paths (e.g. `notes/foo.md`, `other/bar.md`).
-fn relative_path_from(source: &str, target: &str) -> String {
-    let src_dir = match source.rfind('/') {
-        Some(i) => &source[..i],
-        None => "",
+/// Fill in the documentation of a link completion item with a preview of the
+/// target note. Other items are returned unchanged.
+pub fn resolve(state: &BackendState, mut item: CompletionItem) -> CompletionItem {
+    let Some(rel_path) = item
+        .data
+        .as_ref()
+        .and_then(|d| d.get("relPath"))
+        .and_then(|v| v.as_str())
+    else {
+        return item;
     };
-    let tgt_parts: Vec<&str> = target.split('/').collect();
-    let src_parts: Vec<&str> = if src_dir.is_empty() {
-        Vec::new()
-    } else {
-        src_dir.split('/').collect()
+    let Some(collection) = state.get_collection() else {
+        return item;
     };
+    let note_text = crate::collection_utils::uri_from_rel_path(&collection, rel_path)
+        .and_then(|uri| state.document_text(&uri))
+        .or_else(|| std::fs::read_to_string(collection.root.join(rel_path)).ok());
+    if let Some(note_text) = note_text {
+        item.documentation = Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: note_preview(&note_text),
+        }));
+    }
+    item
+}
 
-    // Find the common prefix length
-    let common = src_parts
-        .iter()
-        .zip(tgt_parts.iter())
-        .take_while(|(a, b)| a == b)
-        .count();
-
-    let ups = src_parts.len() - common;
-    let mut parts: Vec<&str> = Vec::new();
-    for _ in 0..ups {
-        parts.push("..");
+/// Most frontmatter fields listed in a link preview.
+const MAX_PREVIEW_FIELDS: usize = 8;
+
+/// Render a note as a frontmatter summary followed by its first paragraph.
+fn note_preview(note_text: &str) -> String {
+    let mut sections = Vec::new();
+
+    let parsed = text::parse_frontmatter(note_text);
+    if let Some(map) = parsed.json.as_object() {
+        let lines: Vec<String> = map
+            .iter()
+            .filter_map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::Array(items) => items
+                        .iter()
+                        .filter_map(scalar_text)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    other => scalar_text(other)?,
+                };
+                (!value.is_empty()).then(|| format!("- **{}**: {}", key, value))
+            })
+            .take(MAX_PREVIEW_FIELDS)
+            .collect();
+        if !lines.is_empty() {
+            sections.push(lines.join("\n"));
+        }
     }
-    for segment in &tgt_parts[common..] {
-        parts.push(segment);
+
+    let body = mdbase::frontmatter::parser::parse_document(note_text).body;
+    if let Some(paragraph) = text::first_paragraph(&body) {
+        sections.push(paragraph);
     }
+    sections.join("\n\n")
+}
 
-    if parts.is_empty() {
-        tgt_parts.last().unwrap_or(&"").to_string()
-    } else {
-        parts.join("/")
+fn scalar_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
     }
 }
@@ -0,0 +1,69 @@
+/// Server settings — read from `initializationOptions` and refreshed from the
+/// `mdbase` section on `workspace/didChangeConfiguration`.
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServerConfig {
+    /// List-of-strings fields that hold tags, in addition to `tags`.
+    pub tag_fields: Vec<String>,
+    /// Frontmatter field listing a note's alternative link names.
+    pub aliases_field: String,
+    /// How to pick between notes sharing a file stem, most important first.
+    /// Remaining ties are broken alphabetically.
+    pub link_tie_break: Vec<LinkTieBreak>,
+    /// Path form for inserted and rewritten links. Unset keeps each link
+    /// format's default (absolute wikilinks, relative markdown links).
+    pub link_style: Option<LinkStyle>,
+    /// Whether link targets keep the `.md` extension. Unset keeps it only for
+    /// markdown links.
+    pub link_extension: Option<bool>,
+}
+
+/// How a link spells its target path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkStyle {
+    /// The bare file stem when no other note shares it, else the absolute path.
+    ShortestUnique,
+    /// The collection-relative path.
+    Absolute,
+    /// The path relative to the linking note.
+    Relative,
+}
+
+/// A criterion for ordering notes that match an ambiguous bare-name link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkTieBreak {
+    /// Notes in the linking note's folder first.
+    SameFolder,
+    /// Shorter collection-relative paths first.
+    ShortestPath,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            tag_fields: Vec::new(),
+            aliases_field: "aliases".to_string(),
+            link_tie_break: vec![LinkTieBreak::SameFolder, LinkTieBreak::ShortestPath],
+            link_style: None,
+            link_extension: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Parse settings from either the bare options object or one nested under
+    /// an `mdbase` key. Invalid settings fall back to the defaults.
+    pub fn from_value(value: &serde_json::Value) -> Self {
+        let section = value.get("mdbase").unwrap_or(value);
+        serde_json::from_value(section.clone()).unwrap_or_default()
+    }
+
+    /// Whether `field` is configured to hold tags (`tags` always is).
+    pub fn is_tag_field(&self, field: &str) -> bool {
+        field == "tags" || self.tag_fields.iter().any(|f| f == field)
+    }
+}
@@ -0,0 +1,425 @@
+/// Date helpers for `date` / `datetime` fields — relative date completions,
+/// human-readable distances for hover, and detection of non-ISO values.
+///
+/// Datetimes use the same `%Y-%m-%dT%H:%M:%SZ` format that
+/// `commands::generate_field_value` writes for `generated: now`.
+use chrono::{Datelike, Local, Months, NaiveDate, NaiveDateTime, TimeDelta, Utc, Weekday};
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::text;
+
+pub(crate) const DATE_FORMAT: &str = "%Y-%m-%d";
+pub(crate) const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Keywords offered as completions, in display order.
+const SUGGESTIONS: &[&str] = &[
+    "today",
+    "tomorrow",
+    "yesterday",
+    "next monday",
+    "next tuesday",
+    "next wednesday",
+    "next thursday",
+    "next friday",
+    "next saturday",
+    "next sunday",
+    "+1d",
+    "+3d",
+    "+1w",
+    "+2w",
+    "+1m",
+];
+
+/// Formats accepted as a non-ISO date and normalized by the quick fix.
+const LOOSE_DATE_FORMATS: &[&str] = &[
+    "%Y/%m/%d",
+    "%Y.%m.%d",
+    "%d.%m.%Y",
+    "%m/%d/%Y",
+    "%d/%m/%Y",
+    "%B %d, %Y",
+    "%b %d, %Y",
+    "%d %B %Y",
+    "%d %b %Y",
+];
+
+/// Formats accepted as a non-ISO datetime (interpreted as UTC).
+const LOOSE_DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+];
+
+pub(crate) fn is_date_type(field_type: &str) -> bool {
+    field_type == "date" || field_type == "datetime"
+}
+
+/// Resolve a relative expression (`today`, `next friday`, `+2w`, `-3d`, …)
+/// against `today`.
+pub(crate) fn parse_relative(expr: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let expr = expr.trim().to_lowercase();
+    match expr.as_str() {
+        "today" | "now" => return Some(today),
+        "tomorrow" => return today.checked_add_signed(TimeDelta::try_days(1)?),
+        "yesterday" => return today.checked_sub_signed(TimeDelta::try_days(1)?),
+        _ => {}
+    }
+    if let Some(day) = expr.strip_prefix("next ") {
+        let weekday: Weekday = day.trim().parse().ok()?;
+        let ahead = (7 + weekday.num_days_from_monday() as i64
+            - today.weekday().num_days_from_monday() as i64)
+            % 7;
+        return today.checked_add_signed(TimeDelta::try_days(if ahead == 0 { 7 } else { ahead })?);
+    }
+
+    let (sign, rest) = match expr.chars().next()? {
+        '+' => (1i64, &expr[1..]),
+        '-' => (-1i64, &expr[1..]),
+        _ => return None,
+    };
+    let unit = rest.chars().last()?;
+    let amount: i64 = rest[..rest.len() - unit.len_utf8()].parse().ok()?;
+    // Typed text reaches here on every keystroke, so out-of-range amounts
+    // must yield `None` rather than panic.
+    match unit {
+        'd' => today.checked_add_signed(TimeDelta::try_days(sign * amount)?),
+        'w' => today.checked_add_signed(TimeDelta::try_weeks(sign * amount)?),
+        'm' | 'y' => {
+            let months = if unit == 'y' {
+                amount.checked_mul(12)?
+            } else {
+                amount
+            };
+            let months = u32::try_from(months).ok()?;
+            if sign > 0 {
+                today.checked_add_months(Months::new(months))
+            } else {
+                today.checked_sub_months(Months::new(months))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Format a date for a `date` or `datetime` field. Datetimes take the
+/// current local time of day on that (local) date, converted to UTC.
+fn format_for_field(date: NaiveDate, field_type: &str) -> String {
+    if field_type == "datetime" {
+        let local = date.and_time(Local::now().time());
+        let utc = local
+            .and_local_timezone(Local)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc).naive_utc())
+            .unwrap_or(local);
+        utc.format(DATETIME_FORMAT).to_string()
+    } else {
+        date.format(DATE_FORMAT).to_string()
+    }
+}
+
+/// Completion items for a date value. `typed` is the text already entered
+/// and `range` the span it covers; each item replaces it with an ISO value.
+pub(crate) fn completion_items(field_type: &str, typed: &str, range: Range) -> Vec<CompletionItem> {
+    let today = Local::now().date_naive();
+    let mut expressions: Vec<String> = Vec::new();
+    let typed = typed.trim();
+    if !typed.is_empty() && parse_relative(typed, today).is_some() && !SUGGESTIONS.contains(&typed)
+    {
+        expressions.push(typed.to_string());
+    }
+    if field_type == "datetime" {
+        expressions.push("now".to_string());
+    }
+    expressions.extend(SUGGESTIONS.iter().map(|s| s.to_string()));
+
+    expressions
+        .into_iter()
+        .enumerate()
+        .filter_map(|(rank, expr)| {
+            let value = if expr == "now" {
+                Utc::now().format(DATETIME_FORMAT).to_string()
+            } else {
+                format_for_field(parse_relative(&expr, today)?, field_type)
+            };
+            Some(CompletionItem {
+                label: expr.clone(),
+                kind: Some(CompletionItemKind::VALUE),
+                detail: Some(value.clone()),
+                filter_text: Some(expr),
+                sort_text: Some(format!("{:03}", rank)),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: value,
+                })),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Parse the date portion of an ISO `date` or `datetime` value.
+pub(crate) fn parse_iso_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    let date_part = value.get(..10)?;
+    if value.len() > 10 && !value[10..].starts_with(['T', ' ']) {
+        return None;
+    }
+    NaiveDate::parse_from_str(date_part, DATE_FORMAT).ok()
+}
+
+/// Human-readable distance from `today`: "today", "in 3 days", "2 months ago".
+pub(crate) fn relative_description(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (date - today).num_days();
+    match days {
+        0 => return "today".to_string(),
+        1 => return "tomorrow".to_string(),
+        -1 => return "yesterday".to_string(),
+        _ => {}
+    }
+    let abs = days.abs();
+    let (amount, unit) = if abs < 14 {
+        (abs, "day")
+    } else if abs < 60 {
+        (abs / 7, "week")
+    } else if abs < 730 {
+        (abs / 30, "month")
+    } else {
+        (abs / 365, "year")
+    };
+    let unit = if amount == 1 {
+        unit.to_string()
+    } else {
+        format!("{}s", unit)
+    };
+    if days > 0 {
+        format!("in {} {}", amount, unit)
+    } else {
+        format!("{} {} ago", amount, unit)
+    }
+}
+
+/// Hover text for a date value, or `None` when it is not an ISO date.
+pub(crate) fn hover_text(field_name: &str, field_type: &str, value: &str) -> Option<String> {
+    let date = parse_iso_date(value)?;
+    let today = Local::now().date_naive();
+    Some(format!(
+        "**{}**: `{}`\n\n{} — {}",
+        field_name,
+        field_type,
+        date.format(DATE_FORMAT),
+        relative_description(date, today)
+    ))
+}
+
+/// Byte range of a scalar `key: value` on a frontmatter line, inside any
+/// quotes. `None` for empty values and flow collections.
+pub(crate) fn scalar_value_range(line: &str) -> Option<(usize, usize)> {
+    let colon_idx = line.find(':')?;
+    let after = &line[colon_idx + 1..];
+    let value = after.trim();
+    if value.is_empty() || value.starts_with(['[', '{', '#']) {
+        return None;
+    }
+    let mut start = colon_idx + 1 + (after.len() - after.trim_start().len());
+    let mut end = start + value.len();
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if quoted {
+        start += 1;
+        end -= 1;
+    }
+    Some((start, end))
+}
+
+/// Whether `value` is already ISO formatted for the field type.
+fn is_iso(value: &str, field_type: &str) -> bool {
+    if field_type == "datetime" {
+        chrono::DateTime::parse_from_rfc3339(value).is_ok()
+            || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").is_ok()
+            || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").is_ok()
+            || NaiveDate::parse_from_str(value, DATE_FORMAT).is_ok()
+    } else {
+        NaiveDate::parse_from_str(value, DATE_FORMAT).is_ok()
+    }
+}
+
+/// The ISO forms of a recognisable non-ISO date, one per reading: a value
+/// such as `03/04/2026` parses both month-first and day-first and yields two.
+/// Empty when the value is already ISO or cannot be parsed.
+pub(crate) fn normalize(value: &str, field_type: &str) -> Vec<String> {
+    let value = value.trim();
+    if value.is_empty() || is_iso(value, field_type) {
+        return Vec::new();
+    }
+    if field_type == "datetime" {
+        if let Some(dt) = LOOSE_DATETIME_FORMATS
+            .iter()
+            .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        {
+            return vec![dt.format(DATETIME_FORMAT).to_string()];
+        }
+    }
+    let mut readings: Vec<String> = Vec::new();
+    for date in LOOSE_DATE_FORMATS
+        .iter()
+        .filter_map(|fmt| NaiveDate::parse_from_str(value, fmt).ok())
+    {
+        let reading = date.format(DATE_FORMAT).to_string();
+        if !readings.contains(&reading) {
+            readings.push(reading);
+        }
+    }
+    readings
+}
+
+/// Warnings for top-level date/datetime values written in a non-ISO format.
+/// Each carries the normalized readings in `data` for the quick fixes.
+pub(crate) fn format_diagnostics(
+    collection: &Collection,
+    text: &str,
+    type_names: &[String],
+) -> Vec<Diagnostic> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for type_name in type_names {
+        let Some(type_def) = collection.types.get(type_name) else {
+            continue;
+        };
+        for (name, def) in &type_def.fields {
+            if is_date_type(&def.field_type) && !fields.iter().any(|(n, _)| n == name) {
+                fields.push((name.clone(), def.field_type.clone()));
+            }
+        }
+    }
+    fields.sort();
+
+    let mut diagnostics = Vec::new();
+    for (field, field_type) in fields {
+        let Some(line_idx) = text::top_level_key_line(text, &field) else {
+            continue;
+        };
+        let line = text.lines().nth(line_idx).unwrap_or("");
+        let Some((start, end)) = scalar_value_range(line) else {
+            continue;
+        };
+        let value = &line[start..end];
+        let normalized = normalize(value, &field_type);
+        let message = match normalized.as_slice() {
+            [] => continue,
+            [single] => format!(
+                "`{}` is not an ISO {} (expected `{}`)",
+                value, field_type, single
+            ),
+            readings => format!(
+                "`{}` is not an ISO {} and is ambiguous: {}",
+                value,
+                field_type,
+                readings
+                    .iter()
+                    .map(|r| format!("`{}`", r))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ),
+        };
+        diagnostics.push(Diagnostic {
+            range: Range::new(
+                Position::new(line_idx as u32, start as u32),
+                Position::new(line_idx as u32, end as u32),
+            ),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("non_iso_date".to_string())),
+            source: Some("mdbase".to_string()),
+            message,
+            data: Some(serde_json::json!({
+                "field": field,
+                "normalized": normalized,
+            })),
+            ..Default::default()
+        });
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn relative_expressions() {
+        // 2026-10-18 is a Sunday.
+        let today = date("2026-10-18");
+        assert_eq!(parse_relative("tomorrow", today), Some(date("2026-10-19")));
+        assert_eq!(
+            parse_relative("next monday", today),
+            Some(date("2026-10-19"))
+        );
+        assert_eq!(
+            parse_relative("next sunday", today),
+            Some(date("2026-10-25"))
+        );
+        assert_eq!(parse_relative("+1w", today), Some(date("2026-10-25")));
+        assert_eq!(parse_relative("-3d", today), Some(date("2026-10-15")));
+        assert_eq!(parse_relative("+1m", today), Some(date("2026-11-18")));
+        assert_eq!(parse_relative("soon", today), None);
+    }
+
+    #[test]
+    fn out_of_range_expressions_are_rejected() {
+        let today = date("2026-10-18");
+        assert_eq!(parse_relative("+99999999d", today), None);
+        assert_eq!(parse_relative("-99999999w", today), None);
+        assert_eq!(parse_relative("+9223372036854775807y", today), None);
+        assert_eq!(parse_relative("+9223372036854775807d", today), None);
+        assert!(completion_items("date", "+99999999d", Range::default())
+            .iter()
+            .all(|item| item.label != "+99999999d"));
+    }
+
+    #[test]
+    fn relative_descriptions() {
+        let today = date("2026-10-18");
+        assert_eq!(relative_description(date("2026-10-21"), today), "in 3 days");
+        assert_eq!(
+            relative_description(date("2026-08-10"), today),
+            "2 months ago"
+        );
+        assert_eq!(relative_description(date("2026-10-17"), today), "yesterday");
+    }
+
+    #[test]
+    fn normalizes_loose_dates() {
+        assert_eq!(normalize("2026/10/18", "date"), vec!["2026-10-18"]);
+        assert_eq!(normalize("Oct 18, 2026", "date"), vec!["2026-10-18"]);
+        assert_eq!(
+            normalize("2026-10-18 09:30", "datetime"),
+            vec!["2026-10-18T09:30:00Z"]
+        );
+        assert!(normalize("2026-10-18", "date").is_empty());
+        assert!(normalize("not a date", "date").is_empty());
+    }
+
+    #[test]
+    fn ambiguous_dates_keep_every_reading() {
+        assert_eq!(normalize("10/18/2026", "date"), vec!["2026-10-18"]);
+        assert_eq!(
+            normalize("03/04/2026", "date"),
+            vec!["2026-03-04", "2026-04-03"]
+        );
+        assert_eq!(normalize("04/04/2026", "date"), vec!["2026-04-04"]);
+    }
+
+    #[test]
+    fn scalar_value_ranges() {
+        assert_eq!(scalar_value_range("due: Oct 18, 2026"), Some((5, 17)));
+        assert_eq!(scalar_value_range("due: \"2026/10/18\""), Some((6, 16)));
+        assert_eq!(scalar_value_range("due: [a]"), None);
+        assert_eq!(scalar_value_range("due:"), None);
+    }
+}
@@ -35,7 +35,8 @@ pub async fn publish(client: &Client, state: &BackendState, uri: &Url) {
     };
 
     let cached = state.documents.get(uri).map(|doc| doc.frontmatter());
-    let diagnostics = compute(&collection, &text, &rel_path, cached);
+    let mut diagnostics = compute(&collection, &text, &rel_path, cached);
+    diagnostics.extend(crate::link_diagnostics::compute(state, &text, &rel_path));
     client
         .publish_diagnostics(uri.clone(), diagnostics, None)
         .await;
@@ -125,7 +126,15 @@ pub(crate) fn compute(
         .and_then(|v| v.as_array())
         .cloned()
         .unwrap_or_default();
-    diagnostics_from_issues(text, issues)
+    let mut diagnostics = diagnostics_from_issues(text, issues);
+
+    let type_names = collection.determine_types_for_path(&parsed.json, Some(rel_path));
+    diagnostics.extend(crate::dates::format_diagnostics(
+        collection,
+        text,
+        &type_names,
+    ));
+    diagnostics
 }
 
 fn diagnostics_from_issues(text: &str, issues: Vec<serde_json::Value>) -> Vec<Diagnostic> {
@@ -158,7 +167,13 @@ fn diagnostic_from_issue(text: &str, fallback_line: usize, issue: serde_json::Va
     };
 
     let range = if let Some(field) = issue.get("field").and_then(|v| v.as_str()) {
-        let (start, end) = text::find_field_range(text, field, fallback_line);
+        let path = text::split_field_path(field);
+        let (start, end) = if path.len() > 1 {
+            text::find_field_path_range(text, &path)
+                .unwrap_or_else(|| text::find_field_range(text, &path[0], fallback_line))
+        } else {
+            text::find_field_range(text, field, fallback_line)
+        };
         Range::new(start, end)
     } else {
         Range::new(
@@ -17,7 +17,7 @@ pub(crate) fn provide(state: &BackendState, uri: &Url) -> Option<Vec<DocumentLin
 
     let mut result = Vec::new();
     for link in &body_links {
-        if let Some(target_url) = link_resolve::resolve_body_link(&collection, uri, link) {
+        if let Some(target_url) = link_resolve::resolve_body_link(state, &collection, uri, link) {
             result.push(DocumentLink {
                 range: Range {
                     start: Position::new(link.start_line as u32, link.start_col as u32),
@@ -0,0 +1,279 @@
+/// Enum value rename — rewrites an allowed value in the `values` list of a
+/// type definition and in every note of the affected types that uses it.
+///
+/// Scoping follows field rename: the topmost type declaring the field plus
+/// its descendants. List fields whose `items` carry `values` are included.
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+
+use mdbase::types::schema::FieldDef;
+use mdbase::Collection;
+
+use crate::collection_utils;
+use crate::field_refs;
+use crate::state::BackendState;
+use crate::text;
+
+/// An enum value at the cursor, with the field it belongs to.
+#[derive(Debug, Clone)]
+pub(crate) struct EnumValueAtCursor {
+    pub field: String,
+    pub value: String,
+    pub range: Range,
+    /// The note's types, or the type being defined in a type file.
+    pub context_types: Vec<String>,
+}
+
+/// Find the enum value under the cursor.
+///
+/// In a type definition this is an entry of a field's `values` list; in a
+/// note it is a value of a field whose definition (or list `items`) restricts
+/// it to `values` that include it.
+pub(crate) fn value_at_position(
+    state: &BackendState,
+    collection: &Collection,
+    uri: &Url,
+    text: &str,
+    position: Position,
+) -> Option<EnumValueAtCursor> {
+    let line_idx = position.line as usize;
+    let column = position.character as usize;
+    let line_text = text.lines().nth(line_idx)?;
+    let (start, end) = text::value_tokens(line_text)
+        .into_iter()
+        .find(|(s, e)| column >= *s && column <= *e)?;
+    let value = line_text[start..end].to_string();
+    let range = Range::new(
+        Position::new(line_idx as u32, start as u32),
+        Position::new(line_idx as u32, end as u32),
+    );
+
+    if let Some(type_name) = collection_utils::type_name_for_uri(collection, uri) {
+        if text::field_name_for_position(text, line_idx).as_deref() != Some("values") {
+            return None;
+        }
+        let field = text::type_field_declarations(text)
+            .into_iter()
+            .rev()
+            .find(|d| d.line < line_idx)?
+            .name;
+        return Some(EnumValueAtCursor {
+            field,
+            value,
+            range,
+            context_types: vec![type_name],
+        });
+    }
+
+    if !text::is_in_frontmatter(text, line_idx) || is_nested_key(line_text) {
+        return None;
+    }
+    let field = text::field_name_for_position(text, line_idx)?;
+    let parsed = state
+        .documents
+        .get(uri)
+        .map(|doc| doc.frontmatter())
+        .unwrap_or_else(|| text::parse_frontmatter(text));
+    if parsed.parse_error || parsed.mapping_error {
+        return None;
+    }
+    let rel_path = collection_utils::rel_path_from_uri(collection, uri);
+    let context_types = collection.determine_types_for_path(&parsed.json, rel_path.as_deref());
+    let def = collection_utils::field_def_for_path(
+        collection,
+        &context_types,
+        std::slice::from_ref(&field),
+    )?;
+    if !allowed_values(&def).contains(&value) {
+        return None;
+    }
+
+    Some(EnumValueAtCursor {
+        field,
+        value,
+        range,
+        context_types,
+    })
+}
+
+/// Build a `WorkspaceEdit` renaming the value in the type definitions that
+/// declare the field (its `values` and `default`) and in every note within
+/// scope.
+pub(crate) fn rename(
+    state: &BackendState,
+    collection: &Collection,
+    found: &EnumValueAtCursor,
+    new_value: &str,
+) -> Option<WorkspaceEdit> {
+    let new_value = new_value.trim();
+    if new_value.is_empty() || new_value.contains([',', '[', ']', '#']) {
+        return None;
+    }
+    let type_decls = collection_utils::TypeDeclarations::new(collection);
+    let scope = field_refs::field_scope(&type_decls, &found.context_types, &found.field);
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for type_name in scope.as_deref().unwrap_or_default() {
+        if !type_decls.declares(type_name, &found.field) {
+            continue;
+        }
+        let Some(path) = type_decls.path(type_name) else {
+            continue;
+        };
+        let Ok(uri) = Url::from_file_path(path) else {
+            continue;
+        };
+        let type_text = state
+            .document_text(&uri)
+            .or_else(|| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let edits = definition_edits(&type_text, &found.field, &found.value, new_value);
+        if !edits.is_empty() {
+            changes.entry(uri).or_default().extend(edits);
+        }
+    }
+
+    for (uri, rel_path, note_text) in collection_utils::collection_texts(state, collection) {
+        if text::top_level_key_line(&note_text, &found.field).is_none() {
+            continue;
+        }
+        if let Some(scope) = scope.as_deref() {
+            let parsed = text::parse_frontmatter(&note_text);
+            if parsed.parse_error || parsed.mapping_error {
+                continue;
+            }
+            let types = collection.determine_types_for_path(&parsed.json, Some(&rel_path));
+            if !types
+                .iter()
+                .any(|t| scope.iter().any(|s| s.eq_ignore_ascii_case(t)))
+            {
+                continue;
+            }
+        }
+        let edits = note_edits(&note_text, &found.field, &found.value, new_value);
+        if !edits.is_empty() {
+            changes.entry(uri).or_default().extend(edits);
+        }
+    }
+
+    Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    })
+}
+
+/// Edits to a type definition: matching entries of the field's own `values`
+/// and `default` keys, or those of its list `items`. Sub-fields declaring
+/// their own `values` are left alone.
+fn definition_edits(text: &str, field: &str, old: &str, new: &str) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let Some(path) = text::field_path_at(text, line_idx) else {
+            continue;
+        };
+        let own_key = match path.segments().as_slice() {
+            [fields, name, key] => fields == "fields" && name == field && is_value_key(key),
+            [fields, name, items, key] => {
+                fields == "fields" && name == field && items == "items" && is_value_key(key)
+            }
+            _ => false,
+        };
+        if own_key {
+            edits.extend(token_edits(line, line_idx, old, new));
+        }
+    }
+    edits
+}
+
+fn is_value_key(key: &str) -> bool {
+    key == "values" || key == "default"
+}
+
+/// Edits to a note: matching values of the top-level field, inline or as
+/// list items.
+fn note_edits(text: &str, field: &str, old: &str, new: &str) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+    let Some((start, end)) = text::frontmatter_bounds(text) else {
+        return edits;
+    };
+    for (line_idx, line) in text.lines().enumerate() {
+        if line_idx < start || line_idx > end {
+            continue;
+        }
+        if is_nested_key(line)
+            || text::field_name_for_position(text, line_idx).as_deref() != Some(field)
+        {
+            continue;
+        }
+        edits.extend(token_edits(line, line_idx, old, new));
+    }
+    edits
+}
+
+/// Whether the line is an indented mapping key rather than a top-level key or
+/// list item.
+fn is_nested_key(line: &str) -> bool {
+    line.starts_with(char::is_whitespace) && !line.trim_start().starts_with('-')
+}
+
+/// Replace every value token on `line` exactly equal to `old`.
+fn token_edits(line: &str, line_idx: usize, old: &str, new: &str) -> Vec<TextEdit> {
+    text::value_tokens(line)
+        .into_iter()
+        .filter(|(s, e)| &line[*s..*e] == old)
+        .map(|(s, e)| TextEdit {
+            range: Range::new(
+                Position::new(line_idx as u32, s as u32),
+                Position::new(line_idx as u32, e as u32),
+            ),
+            new_text: new.to_string(),
+        })
+        .collect()
+}
+
+/// The allowed values of a field, looking through list `items`.
+fn allowed_values(def: &FieldDef) -> &[String] {
+    if let Some(values) = &def.values {
+        return values;
+    }
+    def.items
+        .as_ref()
+        .and_then(|items| items.values.as_deref())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definition_edits_skip_nested_sub_fields() {
+        let text = "\
+fields:
+  status:
+    type: enum
+    values: [open, closed]
+    default: open
+    fields:
+      inner:
+        values: [open]
+        default: open
+  other:
+    values: [open]
+";
+        let lines: Vec<u32> = definition_edits(text, "status", "open", "active")
+            .iter()
+            .map(|e| e.range.start.line)
+            .collect();
+        assert_eq!(lines, vec![3, 4]);
+    }
+
+    #[test]
+    fn definition_edits_cover_list_items_values() {
+        let text = "fields:\n  tags:\n    type: list\n    items:\n      values:\n        - open\n";
+        let edits = definition_edits(text, "tags", "open", "active");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start, Position::new(5, 10));
+    }
+}
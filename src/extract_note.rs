@@ -0,0 +1,235 @@
+/// Extract to new note — a refactor action that moves a selected body range
+/// into a new note and leaves a wikilink or embed in its place.
+///
+/// The new file, its content and the replacement are one `WorkspaceEdit`
+/// with a `CreateFile` operation, so the editor can undo it as a unit.
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::body_links::LinkFormat;
+use crate::collection_utils;
+use crate::commands;
+use crate::link_style;
+use crate::skeleton;
+use crate::state::BackendState;
+use crate::text;
+
+/// Characters that cannot appear in a note file name.
+const INVALID_NAME_CHARS: &[char] = &[
+    '/', '\\', ':', '*', '?', '"', '<', '>', '|', '#', '^', '[', ']',
+];
+
+/// Extract actions for the selected `range`, or none when it is empty or
+/// reaches into the frontmatter.
+///
+/// There is one action leaving a link and one leaving an embed, both
+/// creating the note as the top choice of `collection_utils::new_note_types`.
+pub(crate) fn actions(
+    state: &BackendState,
+    collection: &Collection,
+    uri: &Url,
+    text: &str,
+    rel_path: &str,
+    range: Range,
+) -> Vec<CodeActionOrCommand> {
+    if range.start == range.end {
+        return Vec::new();
+    }
+    let body_start = text::body_start_line(text);
+    if (range.start.line as usize) < body_start {
+        return Vec::new();
+    }
+    let (Some(start), Some(end)) = (
+        text::byte_offset(text, range.start),
+        text::byte_offset(text, range.end),
+    ) else {
+        return Vec::new();
+    };
+    let selected = &text[start..end];
+    let Some(title) = selection_title(selected) else {
+        return Vec::new();
+    };
+    let Some(file_name) = note_file_name(&title) else {
+        return Vec::new();
+    };
+    let dir = rel_path.rsplit_once('/').map(|(d, _)| d);
+    let default_path = match dir {
+        Some(dir) => format!("{}/{}.md", dir, file_name),
+        None => format!("{}.md", file_name),
+    };
+
+    let config = state.config();
+    let existing = state.file_index.with_entries(|entries| {
+        entries
+            .iter()
+            .map(|e| e.rel_path.clone())
+            .collect::<Vec<_>>()
+    });
+
+    // The top-ranked type only; other types can be set on the new note.
+    let type_name = collection_utils::new_note_types(collection, &default_path)
+        .into_iter()
+        .next()
+        .flatten();
+    let (path, frontmatter, label) = match &type_name {
+        Some(type_name) => {
+            let Some(type_def) = collection.types.get(type_name) else {
+                return Vec::new();
+            };
+            let mut fm = serde_json::Map::new();
+            if type_def.fields.contains_key("title") {
+                fm.insert("title".to_string(), serde_json::json!(title));
+            }
+            commands::prefill_frontmatter(collection, type_name, type_def, &mut fm);
+            let path = commands::find_filename_pattern(collection, type_name)
+                .and_then(|pattern| commands::derive_path_from_pattern(&pattern, &fm))
+                .map(|p| {
+                    let p = p.trim_start_matches('/').to_string();
+                    if p.ends_with(".md") {
+                        p
+                    } else {
+                        format!("{}.md", p)
+                    }
+                })
+                .unwrap_or_else(|| default_path.clone());
+            let frontmatter = skeleton::frontmatter_block(collection, type_name, fm);
+            (path, frontmatter, format!(" as {}", type_def.name))
+        }
+        None => (default_path.clone(), String::new(), String::new()),
+    };
+    let path = unique_path(&path, |p| {
+        existing.iter().any(|e| e == p) || collection.root.join(p).exists()
+    });
+    let Some(new_uri) = collection_utils::uri_from_rel_path(collection, &path) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<&str> = existing.iter().map(String::as_str).collect();
+    paths.push(&path);
+    let target =
+        link_style::link_target(&config, LinkFormat::Wikilink, Some(rel_path), &path, &paths);
+    let mut content = frontmatter;
+    content.push_str(selected.trim_end_matches('\n'));
+    content.push('\n');
+
+    let mut actions = Vec::new();
+    for embed in [false, true] {
+        let mut link = format!("{}[[{}]]", if embed { "!" } else { "" }, target);
+        if selected.ends_with('\n') {
+            link.push('\n');
+        }
+        let edit = WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(vec![
+                DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                    uri: new_uri.clone(),
+                    options: Some(CreateFileOptions {
+                        overwrite: Some(false),
+                        ignore_if_exists: Some(false),
+                    }),
+                    annotation_id: None,
+                })),
+                collection_utils::text_document_edit(
+                    new_uri.clone(),
+                    vec![TextEdit {
+                        range: Range::default(),
+                        new_text: content.clone(),
+                    }],
+                ),
+                collection_utils::text_document_edit(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range,
+                        new_text: link,
+                    }],
+                ),
+            ])),
+            ..Default::default()
+        };
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!(
+                "Extract to new note '{}'{}{}",
+                path,
+                label,
+                if embed { " (embed)" } else { "" }
+            ),
+            kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+            edit: Some(edit),
+            ..Default::default()
+        }));
+    }
+    actions
+}
+
+/// The new note's title: the first heading in the selection, or else its
+/// first non-empty line.
+fn selection_title(selected: &str) -> Option<String> {
+    let lines = || selected.lines().map(str::trim).filter(|l| !l.is_empty());
+    let heading = lines().find_map(|line| text::atx_heading(line).map(|(_, t)| t.to_string()));
+    heading.or_else(|| lines().next().map(str::to_string))
+}
+
+/// A file name (without extension) for `title`, with characters that cannot
+/// appear in a note name removed.
+pub(crate) fn note_file_name(title: &str) -> Option<String> {
+    let name: String = title
+        .chars()
+        .filter(|c| !INVALID_NAME_CHARS.contains(c) && !c.is_control())
+        .collect();
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let name = name.trim_matches('.').trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// `path`, or the first of `path 2.md`, `path 3.md`, … that does not exist.
+pub(crate) fn unique_path(path: &str, exists: impl Fn(&str) -> bool) -> String {
+    if !exists(path) {
+        return path.to_string();
+    }
+    let stem = path.strip_suffix(".md").unwrap_or(path);
+    (2..)
+        .map(|n| format!("{} {}.md", stem, n))
+        .find(|candidate| !exists(candidate))
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_prefers_first_heading() {
+        assert_eq!(
+            selection_title("Intro text\n\n## Design notes ##\nmore").as_deref(),
+            Some("Design notes")
+        );
+        assert_eq!(
+            selection_title("\n  First line\nsecond").as_deref(),
+            Some("First line")
+        );
+        assert_eq!(selection_title("#tag line").as_deref(), Some("#tag line"));
+        assert_eq!(selection_title(" \n\n"), None);
+    }
+
+    #[test]
+    fn file_name_drops_invalid_characters() {
+        assert_eq!(
+            note_file_name("Q3: plans / [draft]").as_deref(),
+            Some("Q3 plans draft")
+        );
+        assert_eq!(note_file_name("???"), None);
+    }
+
+    #[test]
+    fn unique_path_appends_counter() {
+        let taken = ["notes/a.md", "notes/a 2.md"];
+        assert_eq!(
+            unique_path("notes/b.md", |p| taken.contains(&p)),
+            "notes/b.md"
+        );
+        assert_eq!(
+            unique_path("notes/a.md", |p| taken.contains(&p)),
+            "notes/a 3.md"
+        );
+    }
+}
@@ -0,0 +1,367 @@
+/// Field name references — finds and renames frontmatter keys across the notes
+/// of related types and in their `_types/` definitions.
+///
+/// A field's scope is the topmost type declaring it plus every type extending
+/// that one, so unrelated types with a same-named field are left alone.
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::collection_utils;
+use crate::state::BackendState;
+use crate::text;
+
+/// A frontmatter key (or type definition field declaration) at the cursor.
+#[derive(Debug, Clone)]
+pub(crate) struct FieldAtCursor {
+    pub name: String,
+    pub range: Range,
+    /// The note's types, or the type being defined in a type file.
+    pub context_types: Vec<String>,
+}
+
+/// Find the field key under the cursor.
+///
+/// In a note this is a top-level `key:` with the cursor on the key; in a type
+/// definition it is a field declared under `fields:`. Type keys are skipped.
+pub(crate) fn field_at_position(
+    state: &BackendState,
+    collection: &Collection,
+    uri: &Url,
+    text: &str,
+    position: Position,
+) -> Option<FieldAtCursor> {
+    let line_idx = position.line as usize;
+    let column = position.character as usize;
+
+    if let Some(type_name) = collection_utils::type_name_for_uri(collection, uri) {
+        let decl = text::type_field_declarations(text)
+            .into_iter()
+            .find(|d| d.line == line_idx && column >= d.start_col && column <= d.end_col)?;
+        return Some(FieldAtCursor {
+            range: Range::new(
+                Position::new(decl.line as u32, decl.start_col as u32),
+                Position::new(decl.line as u32, decl.end_col as u32),
+            ),
+            name: decl.name,
+            context_types: vec![type_name],
+        });
+    }
+
+    if !text::is_in_frontmatter(text, line_idx) {
+        return None;
+    }
+    let line_text = text.lines().nth(line_idx)?;
+    if line_text.starts_with(char::is_whitespace) || line_text.starts_with('-') {
+        return None;
+    }
+    let colon_idx = line_text.find(':')?;
+    if column > colon_idx {
+        return None;
+    }
+    let name = line_text[..colon_idx].trim_end();
+    if name.is_empty() || collection_utils::is_type_key(collection, name) {
+        return None;
+    }
+
+    let parsed = state
+        .documents
+        .get(uri)
+        .map(|doc| doc.frontmatter())
+        .unwrap_or_else(|| text::parse_frontmatter(text));
+    if parsed.parse_error || parsed.mapping_error {
+        return None;
+    }
+    let rel_path = collection_utils::rel_path_from_uri(collection, uri);
+    let context_types = collection.determine_types_for_path(&parsed.json, rel_path.as_deref());
+
+    Some(FieldAtCursor {
+        name: name.to_string(),
+        range: Range::new(
+            Position::new(line_idx as u32, 0),
+            Position::new(line_idx as u32, name.len() as u32),
+        ),
+        context_types,
+    })
+}
+
+/// Locations of the field: its declarations in type definitions (when
+/// `include_declaration` is set) followed by every note that sets it.
+pub(crate) fn references(
+    state: &BackendState,
+    collection: &Collection,
+    field: &FieldAtCursor,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let type_decls = collection_utils::TypeDeclarations::new(collection);
+    let scope = field_scope(&type_decls, &field.context_types, &field.name);
+    let mut locations = Vec::new();
+    if include_declaration {
+        for (uri, decl) in declarations(state, &type_decls, scope.as_deref(), &field.name) {
+            locations.push(Location {
+                uri,
+                range: Range::new(
+                    Position::new(decl.line as u32, decl.start_col as u32),
+                    Position::new(decl.line as u32, decl.end_col as u32),
+                ),
+            });
+        }
+    }
+    locations.extend(
+        note_occurrences(state, collection, scope.as_deref(), &field.name)
+            .into_iter()
+            .map(|occurrence| occurrence.location),
+    );
+    locations
+}
+
+/// Build a `WorkspaceEdit` renaming the field key in every note within scope
+/// and in the type definitions that declare it. Without a type context only
+/// untyped notes are renamed.
+///
+/// Fails when a note or type in scope already has a field named `new_name`,
+/// since the rename would leave a duplicate key.
+pub(crate) fn rename(
+    state: &BackendState,
+    collection: &Collection,
+    field: &FieldAtCursor,
+    new_name: &str,
+) -> Result<Option<WorkspaceEdit>> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() || new_name.contains(':') || new_name == field.name {
+        return Ok(None);
+    }
+    let type_decls = collection_utils::TypeDeclarations::new(collection);
+    let scope = field_scope(&type_decls, &field.context_types, &field.name);
+
+    if let Some(type_name) = scope
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .find(|t| topmost_declaring_type(&type_decls, t, new_name).is_some())
+    {
+        return Err(Error::invalid_params(format!(
+            "Type '{}' already has a field '{}'",
+            type_name, new_name
+        )));
+    }
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for occurrence in note_occurrences(state, collection, scope.as_deref(), &field.name) {
+        if scope.is_none() && !occurrence.types.is_empty() {
+            continue;
+        }
+        if text::top_level_key_line(&occurrence.text, new_name).is_some() {
+            return Err(Error::invalid_params(format!(
+                "'{}' already has a field '{}'",
+                occurrence.rel_path, new_name
+            )));
+        }
+        changes
+            .entry(occurrence.location.uri)
+            .or_default()
+            .push(TextEdit {
+                range: occurrence.location.range,
+                new_text: new_name.to_string(),
+            });
+    }
+
+    for (uri, decl) in declarations(state, &type_decls, scope.as_deref(), &field.name) {
+        changes.entry(uri).or_default().push(TextEdit {
+            range: Range::new(
+                Position::new(decl.line as u32, decl.start_col as u32),
+                Position::new(decl.line as u32, decl.end_col as u32),
+            ),
+            new_text: new_name.to_string(),
+        });
+    }
+
+    // Keep `{field}` placeholders in filename patterns in sync, including
+    // those of subtypes that only inherit the field.
+    let old_placeholder = format!("{{{}}}", field.name);
+    for type_name in scope.as_deref().unwrap_or_default() {
+        let Some(path) = type_decls.path(type_name) else {
+            continue;
+        };
+        let Ok(uri) = Url::from_file_path(path) else {
+            continue;
+        };
+        let type_text = state
+            .document_text(&uri)
+            .or_else(|| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        for (line_idx, line) in type_text.lines().enumerate() {
+            if text::field_name_from_line(line).as_deref() != Some("filename_pattern") {
+                continue;
+            }
+            for (col, _) in line.match_indices(&old_placeholder) {
+                changes.entry(uri.clone()).or_default().push(TextEdit {
+                    range: Range::new(
+                        Position::new(line_idx as u32, (col + 1) as u32),
+                        Position::new(line_idx as u32, (col + 1 + field.name.len()) as u32),
+                    ),
+                    new_text: new_name.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }))
+}
+
+/// Types sharing the field: the topmost ancestor declaring it for each
+/// context type, plus all of their descendants.
+///
+/// Falls back to the context types themselves when none declares the field,
+/// and to `None` (every note) when there is no type context at all.
+pub(crate) fn field_scope(
+    type_decls: &collection_utils::TypeDeclarations,
+    context_types: &[String],
+    field_name: &str,
+) -> Option<Vec<String>> {
+    let collection = type_decls.collection();
+    let mut roots: Vec<String> = Vec::new();
+    for type_name in context_types {
+        if let Some(root) = topmost_declaring_type(type_decls, type_name, field_name) {
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+    }
+    if roots.is_empty() {
+        return if context_types.is_empty() {
+            None
+        } else {
+            Some(context_types.to_vec())
+        };
+    }
+
+    let mut scope: Vec<String> = Vec::new();
+    let mut pending = roots;
+    while let Some(name) = pending.pop() {
+        if scope.contains(&name) {
+            continue;
+        }
+        pending.extend(collection_utils::child_types(collection, &name));
+        scope.push(name);
+    }
+    scope.sort();
+    Some(scope)
+}
+
+/// Walk the extends chain from `type_name` and return the highest ancestor
+/// that declares `field_name`.
+fn topmost_declaring_type(
+    type_decls: &collection_utils::TypeDeclarations,
+    type_name: &str,
+    field_name: &str,
+) -> Option<String> {
+    let collection = type_decls.collection();
+    let mut found = None;
+    let mut visited = HashSet::new();
+    let mut current = Some(type_name.to_lowercase());
+    while let Some(name) = current {
+        if !visited.insert(name.clone()) {
+            break;
+        }
+        let Some(type_def) = collection.types.get(&name) else {
+            break;
+        };
+        if type_decls.declares(&name, field_name) {
+            found = Some(name.clone());
+        }
+        current = type_def.extends.as_ref().map(|p| p.to_lowercase());
+    }
+    found
+}
+
+/// Field declarations in the type definitions within scope, with each type
+/// file's URI.
+fn declarations(
+    state: &BackendState,
+    type_decls: &collection_utils::TypeDeclarations,
+    scope: Option<&[String]>,
+    field_name: &str,
+) -> Vec<(Url, text::FieldDeclaration)> {
+    let mut result = Vec::new();
+    for type_name in scope.unwrap_or_default() {
+        if !type_decls.declares(type_name, field_name) {
+            continue;
+        }
+        let Some(path) = type_decls.path(type_name) else {
+            continue;
+        };
+        let Ok(uri) = Url::from_file_path(path) else {
+            continue;
+        };
+        let type_text = state
+            .document_text(&uri)
+            .or_else(|| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        if let Some(decl) = text::type_field_declarations(&type_text)
+            .into_iter()
+            .find(|d| d.name == field_name)
+        {
+            result.push((uri, decl));
+        }
+    }
+    result
+}
+
+/// A note that sets the field.
+struct NoteOccurrence {
+    /// The field's key.
+    location: Location,
+    rel_path: String,
+    /// The note's types.
+    types: Vec<String>,
+    text: String,
+}
+
+/// Every note within scope whose frontmatter sets `field_name` as a
+/// top-level key, pointing at the key.
+fn note_occurrences(
+    state: &BackendState,
+    collection: &Collection,
+    scope: Option<&[String]>,
+    field_name: &str,
+) -> Vec<NoteOccurrence> {
+    let mut occurrences = Vec::new();
+    for (file_uri, rel_path, text) in collection_utils::collection_texts(state, collection) {
+        let Some(line_idx) = text::top_level_key_line(&text, field_name) else {
+            continue;
+        };
+        let parsed = text::parse_frontmatter(&text);
+        if parsed.parse_error || parsed.mapping_error {
+            continue;
+        }
+        let types = collection.determine_types_for_path(&parsed.json, Some(&rel_path));
+        if let Some(scope) = scope {
+            let in_scope = types
+                .iter()
+                .any(|t| scope.iter().any(|s| s.eq_ignore_ascii_case(t)));
+            if !in_scope {
+                continue;
+            }
+        }
+        occurrences.push(NoteOccurrence {
+            location: Location {
+                uri: file_uri,
+                range: Range::new(
+                    Position::new(line_idx as u32, 0),
+                    Position::new(line_idx as u32, field_name.len() as u32),
+                ),
+            },
+            rel_path,
+            types,
+            text,
+        });
+    }
+    occurrences
+}
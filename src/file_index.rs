@@ -1,9 +1,12 @@
-use std::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
 
 use mdbase::Collection;
 use tracing::debug;
 
 use crate::collection_utils;
+use crate::config::LinkTieBreak;
+use crate::link_resolve::IndexResolver;
 use crate::text;
 
 #[derive(Debug, Clone)]
@@ -14,23 +17,45 @@ pub(crate) struct FileEntry {
     pub display_name: Option<String>,
     pub title: Option<String>,
     pub id: Option<String>,
-    pub preview: Option<String>,
+    /// Alternative names from the configured aliases field.
+    pub aliases: Vec<String>,
+    /// Raw link targets in the note — body links and frontmatter link values.
+    pub links: Vec<String>,
+    /// Short string values of top-level frontmatter fields (list items
+    /// included), for value frequency lookups.
+    pub field_values: HashMap<String, Vec<String>>,
+}
+
+/// Resolved link targets of each indexed note, by rel_path.
+pub(crate) type LinkGraph = HashMap<String, HashSet<String>>;
+
+/// The link graph kept between requests, so ranking does not re-resolve
+/// every link in the collection on each completion.
+#[derive(Default)]
+struct LinkCache {
+    /// `None` until first needed, and again after any change that can alter
+    /// how other notes' links resolve (notes added or removed, aliases).
+    graph: Option<LinkGraph>,
+    /// Notes whose own links changed since the graph was built.
+    stale: HashSet<String>,
 }
 
 pub(crate) struct FileIndex {
     entries: RwLock<Vec<FileEntry>>,
+    links: Mutex<LinkCache>,
 }
 
 impl FileIndex {
     pub fn new() -> Self {
         Self {
             entries: RwLock::new(Vec::new()),
+            links: Mutex::default(),
         }
     }
 
     /// Full scan of the collection — reads every file's frontmatter and body.
     /// Call from a blocking context (spawn_blocking).
-    pub fn rebuild(&self, collection: &Collection) {
+    pub fn rebuild(&self, collection: &Collection, aliases_field: &str) {
         let files = collection_utils::scan_collection_files(collection);
         let mut entries = Vec::with_capacity(files.len());
 
@@ -49,29 +74,60 @@ impl FileIndex {
                 continue;
             }
 
-            if let Some(entry) = build_entry(collection, rel_path, &content, &parsed.json) {
+            if let Some(entry) =
+                build_entry(collection, rel_path, &content, &parsed.json, aliases_field)
+            {
                 entries.push(entry);
             }
         }
 
         debug!(count = entries.len(), "file_index: rebuilt");
         *self.entries.write().unwrap() = entries;
+        self.invalidate_links();
     }
 
     /// Upsert a single file entry from in-memory text.
-    pub fn upsert_from_text(&self, collection: &Collection, rel_path: String, text: &str) {
+    pub fn upsert_from_text(
+        &self,
+        collection: &Collection,
+        rel_path: String,
+        text: &str,
+        aliases_field: &str,
+    ) {
         let parsed = text::parse_frontmatter(text);
         if parsed.parse_error || parsed.mapping_error {
             return;
         }
-        let Some(entry) = build_entry(collection, rel_path.clone(), text, &parsed.json) else {
+        let Some(entry) = build_entry(
+            collection,
+            rel_path.clone(),
+            text,
+            &parsed.json,
+            aliases_field,
+        ) else {
             return;
         };
+        self.replace_entry(entry);
+    }
+
+    fn replace_entry(&self, entry: FileEntry) {
         let mut entries = self.entries.write().unwrap();
-        if let Some(existing) = entries.iter_mut().find(|e| e.rel_path == rel_path) {
-            *existing = entry;
-        } else {
-            entries.push(entry);
+        let mut links = self.links.lock().unwrap();
+        match entries.iter_mut().find(|e| e.rel_path == entry.rel_path) {
+            Some(existing) if existing.aliases == entry.aliases => {
+                if existing.links != entry.links {
+                    links.stale.insert(entry.rel_path.clone());
+                }
+                *existing = entry;
+            }
+            Some(existing) => {
+                *existing = entry;
+                *links = LinkCache::default();
+            }
+            None => {
+                entries.push(entry);
+                *links = LinkCache::default();
+            }
         }
     }
 
@@ -79,6 +135,38 @@ impl FileIndex {
     pub fn remove_path(&self, rel_path: &str) {
         let mut entries = self.entries.write().unwrap();
         entries.retain(|e| e.rel_path != rel_path);
+        *self.links.lock().unwrap() = LinkCache::default();
+    }
+
+    /// Drop the cached link graph, e.g. when link resolution settings change.
+    pub fn invalidate_links(&self) {
+        *self.links.lock().unwrap() = LinkCache::default();
+    }
+
+    /// Run `f` over the resolved links of every note. Only notes changed
+    /// since the last call are resolved again; `entries` is the caller's view
+    /// from `with_entries`, and `resolver` is built over it.
+    pub fn with_link_graph<R>(
+        &self,
+        entries: &[FileEntry],
+        resolver: &IndexResolver,
+        f: impl FnOnce(&LinkGraph) -> R,
+    ) -> R {
+        let mut links = self.links.lock().unwrap();
+        let LinkCache { graph, stale } = &mut *links;
+        if let Some(graph) = graph.as_mut() {
+            for entry in entries.iter().filter(|e| stale.contains(&e.rel_path)) {
+                graph.insert(entry.rel_path.clone(), resolved_links(entry, resolver));
+            }
+        }
+        let graph = graph.get_or_insert_with(|| {
+            entries
+                .iter()
+                .map(|e| (e.rel_path.clone(), resolved_links(e, resolver)))
+                .collect()
+        });
+        stale.clear();
+        f(graph)
     }
 
     /// Return rel_paths that match `target_type` (or all files if None).
@@ -94,38 +182,93 @@ impl FileIndex {
             .collect()
     }
 
-    /// Return rel_paths with optional display names that match `target_type`.
-    pub fn link_targets_with_display(
+    /// Look up a single entry by its collection-relative path.
+    pub fn entry(&self, rel_path: &str) -> Option<FileEntry> {
+        let entries = self.entries.read().unwrap();
+        entries.iter().find(|e| e.rel_path == rel_path).cloned()
+    }
+
+    /// The note with alias `name` (case-insensitive), as linked from
+    /// `source_rel_path`. When several notes share the alias, the first in
+    /// `tie_break` order wins, as for shared stems.
+    pub fn resolve_alias(
         &self,
-        target_type: Option<&str>,
-    ) -> Vec<(String, Option<String>, Option<String>)> {
+        name: &str,
+        source_rel_path: Option<&str>,
+        tie_break: &[LinkTieBreak],
+    ) -> Option<String> {
         let entries = self.entries.read().unwrap();
-        entries
+        let owners: Vec<String> = entries
             .iter()
-            .filter(|e| match target_type {
-                Some(tt) => e.types.iter().any(|t| t.eq_ignore_ascii_case(tt)),
-                None => true,
-            })
-            .map(|e| {
-                (
-                    e.rel_path.clone(),
-                    e.display_name.clone(),
-                    e.preview.clone(),
-                )
-            })
-            .collect()
+            .filter(|e| e.aliases.iter().any(|a| a.eq_ignore_ascii_case(name)))
+            .map(|e| e.rel_path.clone())
+            .collect();
+        collection_utils::order_stem_matches(owners, source_rel_path, tie_break)
+            .into_iter()
+            .next()
+    }
+
+    /// Other notes (not `rel_path`) that declare alias `name`, sorted.
+    pub fn alias_owners(&self, name: &str, rel_path: &str) -> Vec<String> {
+        let entries = self.entries.read().unwrap();
+        let mut owners: Vec<String> = entries
+            .iter()
+            .filter(|e| e.rel_path != rel_path)
+            .filter(|e| e.aliases.iter().any(|a| a.eq_ignore_ascii_case(name)))
+            .map(|e| e.rel_path.clone())
+            .collect();
+        owners.sort();
+        owners
+    }
+
+    /// Run `f` over the entries without cloning them.
+    pub fn with_entries<R>(&self, f: impl FnOnce(&[FileEntry]) -> R) -> R {
+        f(&self.entries.read().unwrap())
     }
 
     pub fn all_entries(&self) -> Vec<FileEntry> {
         self.entries.read().unwrap().clone()
     }
 
+    /// Number of notes using each tag. Nested tags (`a/b`) also count toward
+    /// their parents, once per note.
     pub fn tag_counts(&self) -> Vec<(String, usize)> {
         let entries = self.entries.read().unwrap();
         let mut counts = std::collections::HashMap::<String, usize>::new();
         for entry in entries.iter() {
+            let mut seen = std::collections::HashSet::new();
             for tag in &entry.tags {
-                *counts.entry(tag.clone()).or_default() += 1;
+                for name in crate::tag_refs::tag_with_ancestors(tag) {
+                    if seen.insert(name) {
+                        *counts.entry(name.to_string()).or_default() += 1;
+                    }
+                }
+            }
+        }
+        let mut result: Vec<(String, usize)> = counts.into_iter().collect();
+        result.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        result
+    }
+
+    /// Number of notes using each value of `field`, most common first.
+    /// Only notes sharing one of `types` count, unless `types` is empty.
+    pub fn field_value_counts(&self, field: &str, types: &[String]) -> Vec<(String, usize)> {
+        let entries = self.entries.read().unwrap();
+        let mut counts = HashMap::<String, usize>::new();
+        for entry in entries.iter() {
+            let same_type = types.is_empty()
+                || entry
+                    .types
+                    .iter()
+                    .any(|t| types.iter().any(|tt| tt.eq_ignore_ascii_case(t)));
+            if !same_type {
+                continue;
+            }
+            let Some(values) = entry.field_values.get(field) else {
+                continue;
+            };
+            for value in values {
+                *counts.entry(value.clone()).or_default() += 1;
             }
         }
         let mut result: Vec<(String, usize)> = counts.into_iter().collect();
@@ -134,11 +277,25 @@ impl FileIndex {
     }
 }
 
+/// Longest string value kept in `FileEntry::field_values`.
+const MAX_FIELD_VALUE_LEN: usize = 100;
+
+/// The notes `entry` links to, as resolved by `resolver`.
+pub(crate) fn resolved_links(entry: &FileEntry, resolver: &IndexResolver) -> HashSet<String> {
+    entry
+        .links
+        .iter()
+        .filter_map(|t| resolver.resolve(t, Some(&entry.rel_path)))
+        .map(str::to_string)
+        .collect()
+}
+
 fn build_entry(
     collection: &Collection,
     rel_path: String,
     content: &str,
     frontmatter: &serde_json::Value,
+    aliases_field: &str,
 ) -> Option<FileEntry> {
     let types = collection.determine_types_for_path(frontmatter, Some(&rel_path));
     let title = json_string(frontmatter, "title");
@@ -147,8 +304,10 @@ fn build_entry(
         .clone()
         .or_else(|| json_string(frontmatter, "name"))
         .or_else(|| id.clone());
-    let preview = build_preview(content);
     let tags = collect_tags(content, frontmatter);
+    let field_values = collect_field_values(collection, frontmatter);
+    let aliases = json_strings(frontmatter, aliases_field);
+    let links = collect_links(content, frontmatter);
     Some(FileEntry {
         rel_path,
         types,
@@ -156,22 +315,12 @@ fn build_entry(
         display_name,
         title,
         id,
-        preview,
+        aliases,
+        links,
+        field_values,
     })
 }
 
-fn build_preview(content: &str) -> Option<String> {
-    let max_chars = 2000usize;
-    if content.is_empty() {
-        return None;
-    }
-    let mut preview: String = content.chars().take(max_chars).collect();
-    if content.chars().count() > max_chars {
-        preview.push_str("...");
-    }
-    Some(preview)
-}
-
 fn collect_tags(content: &str, frontmatter: &serde_json::Value) -> Vec<String> {
     let mut tags = Vec::new();
     if let Some(arr) = frontmatter.get("tags").and_then(|v| v.as_array()) {
@@ -197,6 +346,81 @@ fn collect_tags(content: &str, frontmatter: &serde_json::Value) -> Vec<String> {
     tags
 }
 
+fn collect_field_values(
+    collection: &Collection,
+    frontmatter: &serde_json::Value,
+) -> HashMap<String, Vec<String>> {
+    let mut result = HashMap::new();
+    let Some(map) = frontmatter.as_object() else {
+        return result;
+    };
+    for (key, value) in map {
+        if collection_utils::is_type_key(collection, key) {
+            continue;
+        }
+        let items = match value {
+            serde_json::Value::Array(arr) => arr.iter().collect(),
+            other => vec![other],
+        };
+        let mut values: Vec<String> = Vec::new();
+        for item in items {
+            let Some(s) = item.as_str().map(str::trim) else {
+                continue;
+            };
+            if s.is_empty() || s.len() > MAX_FIELD_VALUE_LEN || values.iter().any(|v| v == s) {
+                continue;
+            }
+            values.push(s.to_string());
+        }
+        if !values.is_empty() {
+            result.insert(key.clone(), values);
+        }
+    }
+    result
+}
+
+fn collect_links(content: &str, frontmatter: &serde_json::Value) -> Vec<String> {
+    let mut links: Vec<String> = crate::body_links::find_body_links(content)
+        .into_iter()
+        .map(|link| link.target)
+        .collect();
+    if let Some(map) = frontmatter.as_object() {
+        for value in map.values() {
+            let items = match value {
+                serde_json::Value::Array(arr) => arr.iter().collect(),
+                other => vec![other],
+            };
+            for item in items {
+                let Some(s) = item.as_str() else {
+                    continue;
+                };
+                if s.trim_start().starts_with("[[") {
+                    links.extend(collection_utils::parse_link_value(s));
+                }
+            }
+        }
+    }
+    links.sort();
+    links.dedup();
+    links
+}
+
+/// A string or list-of-strings frontmatter field, as a list.
+fn json_strings(frontmatter: &serde_json::Value, key: &str) -> Vec<String> {
+    let values = match frontmatter.get(key) {
+        Some(serde_json::Value::Array(arr)) => arr.iter().collect(),
+        Some(other) => vec![other],
+        None => Vec::new(),
+    };
+    values
+        .into_iter()
+        .filter_map(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 fn json_string(frontmatter: &serde_json::Value, key: &str) -> Option<String> {
     let value = frontmatter.get(key)?.as_str()?.trim();
     if value.is_empty() {
@@ -205,3 +429,75 @@ fn json_string(frontmatter: &serde_json::Value, key: &str) -> Option<String> {
         Some(value.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(rel_path: &str, aliases: &[&str]) -> FileEntry {
+        FileEntry {
+            rel_path: rel_path.to_string(),
+            types: Vec::new(),
+            tags: Vec::new(),
+            display_name: None,
+            title: None,
+            id: None,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            links: Vec::new(),
+            field_values: Default::default(),
+        }
+    }
+
+    #[test]
+    fn shared_aliases_resolve_alike_in_index_and_resolver() {
+        let entries = vec![
+            entry("b/js.md", &["Script"]),
+            entry("a/long/javascript.md", &["Script"]),
+        ];
+        let index = FileIndex {
+            entries: RwLock::new(entries.clone()),
+            links: Mutex::default(),
+        };
+        let tie_break = [LinkTieBreak::SameFolder, LinkTieBreak::ShortestPath];
+        let resolver = IndexResolver::new(&entries, &[], &tie_break);
+        for (source, expected) in [
+            ("a/long/note.md", "a/long/javascript.md"),
+            ("b/note.md", "b/js.md"),
+            ("c/note.md", "b/js.md"),
+        ] {
+            assert_eq!(
+                index
+                    .resolve_alias("script", Some(source), &tie_break)
+                    .as_deref(),
+                Some(expected)
+            );
+            assert_eq!(resolver.resolve("Script", Some(source)), Some(expected));
+        }
+    }
+
+    #[test]
+    fn link_graph_follows_entry_changes() {
+        let index = FileIndex::new();
+        index.replace_entry(entry("a.md", &[]));
+        index.replace_entry(entry("b.md", &["Bee"]));
+        let graph = |index: &FileIndex| {
+            index.with_entries(|entries| {
+                let resolver = IndexResolver::new(entries, &[], &[]);
+                index.with_link_graph(entries, &resolver, |graph| graph.clone())
+            })
+        };
+        assert!(graph(&index)["a.md"].is_empty());
+
+        // Only the edited note's links change.
+        let mut edited = entry("a.md", &[]);
+        edited.links = vec!["Bee".to_string()];
+        index.replace_entry(edited);
+        assert!(index.links.lock().unwrap().graph.is_some());
+        assert!(graph(&index)["a.md"].contains("b.md"));
+
+        // A new note can change what existing links resolve to.
+        index.replace_entry(entry("c.md", &[]));
+        assert!(index.links.lock().unwrap().graph.is_none());
+        assert_eq!(graph(&index).len(), 3);
+    }
+}
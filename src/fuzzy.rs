@@ -0,0 +1,97 @@
+//! Fuzzy matching for completion ranking and link suggestions.
+
+/// Score `candidate` against `query` as a case-insensitive subsequence match.
+///
+/// Returns `None` when some query character has no match. Higher scores mean
+/// better matches: consecutive runs, matches at word starts and a matching
+/// prefix are rewarded, and longer candidates are slightly penalized. An
+/// empty query matches everything with a score of zero.
+pub(crate) fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0i64;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+    for (ci, &c) in chars.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if c != query[qi] {
+            continue;
+        }
+        score += 1;
+        if prev_match.is_some_and(|p| p + 1 == ci) {
+            score += 5;
+        }
+        if ci == 0 || is_word_separator(chars[ci - 1]) {
+            score += 8;
+        }
+        prev_match = Some(ci);
+        qi += 1;
+    }
+    if qi < query.len() {
+        return None;
+    }
+    if chars.starts_with(&query) {
+        score += 10;
+        if chars.len() == query.len() {
+            score += 20;
+        }
+    }
+    score -= (chars.len() as i64 - query.len() as i64) / 4;
+    Some(score)
+}
+
+/// Case-insensitive Levenshtein distance between two strings, in characters.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            row[j + 1] = substitution.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev[b.len()]
+}
+
+fn is_word_separator(c: char) -> bool {
+    matches!(c, ' ' | '-' | '_' | '/' | '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_every_query_character_in_order() {
+        assert!(fuzzy_score("mtg", "meeting").is_some());
+        assert!(fuzzy_score("gtm", "meeting").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn prefers_prefix_and_word_start_matches() {
+        let prefix = fuzzy_score("pro", "project plan").unwrap();
+        let word = fuzzy_score("pla", "project plan").unwrap();
+        let scattered = fuzzy_score("pjt", "project plan").unwrap();
+        assert!(prefix > scattered);
+        assert!(word > scattered);
+        assert!(fuzzy_score("plan", "plan").unwrap() > fuzzy_score("plan", "planning").unwrap());
+    }
+
+    #[test]
+    fn edit_distance_ignores_case() {
+        assert_eq!(edit_distance("Projcet Plan", "project plan"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "SAME"), 0);
+    }
+}
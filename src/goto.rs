@@ -5,6 +5,7 @@ use tower_lsp::lsp_types::*;
 use tracing::debug;
 
 use crate::collection_utils;
+use crate::link_resolve;
 use crate::state::BackendState;
 use crate::text;
 
@@ -44,12 +45,20 @@ pub fn definition(
         )
     } else {
         debug!(line = line_idx, col = column, "goto: cursor in body");
-        definition_in_body(&collection, &text, line_idx, column, rel_path.as_deref())
+        definition_in_body(
+            state,
+            &collection,
+            &text,
+            line_idx,
+            column,
+            rel_path.as_deref(),
+        )
     }
 }
 
 /// Handle go-to-definition for a cursor position in the document body.
 fn definition_in_body(
+    state: &BackendState,
     collection: &Arc<mdbase::Collection>,
     text: &str,
     line_idx: usize,
@@ -59,14 +68,14 @@ fn definition_in_body(
     // Use body_links parser (respects fenced code blocks and inline code spans)
     if let Some(link) = crate::body_links::body_link_at(text, line_idx, column) {
         debug!(target = %link.target, "goto body: found body link at cursor");
-        let resolved = collection_utils::resolve_link_target(collection, &link.target, rel_path)?;
+        let resolved = link_resolve::resolve_target(state, collection, &link.target, rel_path)?;
         return make_location_response(&resolved);
     }
 
     // Fall back to line-level link detection
     let link = text::link_at_position(text, line_idx, column)?;
     debug!(target = %link.target, "goto body: found link at cursor (line fallback)");
-    let resolved = collection_utils::resolve_link_target(collection, &link.target, rel_path)?;
+    let resolved = link_resolve::resolve_target(state, collection, &link.target, rel_path)?;
     make_location_response(&resolved)
 }
 
@@ -84,7 +93,7 @@ fn definition_in_frontmatter(
     if let Some(link) = text::link_at_position(text, line_idx, column) {
         debug!(target = %link.target, "goto fm: inline link at cursor");
         if let Some(resolved) =
-            collection_utils::resolve_link_target(collection, &link.target, rel_path)
+            link_resolve::resolve_target(state, collection, &link.target, rel_path)
         {
             return make_location_response(&resolved);
         }
@@ -106,7 +115,21 @@ fn definition_in_frontmatter(
     }
     let type_names = collection.determine_types_for_path(&parsed.json, rel_path);
 
-    // 4. Type/types field → jump to type definition
+    // 4. Cursor on a field key → jump to the field's declaration in the type file
+    let line_text = text.lines().nth(line_idx).unwrap_or("");
+    if let Some(colon_idx) = line_text.find(':') {
+        let is_key_line = text::field_name_from_line(line_text).as_deref() == Some(&field_name)
+            && !line_text.trim_start().starts_with('-');
+        if is_key_line && column <= colon_idx {
+            let path = text::field_path_at(text, line_idx)
+                .map(|p| p.segments())
+                .unwrap_or_else(|| vec![field_name.clone()]);
+            debug!(field = %path.join("."), "goto fm: looking up field declaration");
+            return field_definition_response(state, collection, &type_names, &path);
+        }
+    }
+
+    // 5. Type/types field → jump to type definition
     if field_name == "type" || field_name == "types" {
         if let Some(word) = text::word_at(text.lines().nth(line_idx).unwrap_or(""), column) {
             debug!(type_name = %word, "goto fm: looking up type definition");
@@ -118,7 +141,7 @@ fn definition_in_frontmatter(
         return None;
     }
 
-    // 5. Link-type field → resolve the value as a link target
+    // 6. Link-type field → resolve the value as a link target
     if is_link_field(collection, &type_names, &field_name) {
         let line_text = text.lines().nth(line_idx).unwrap_or("");
         if let Some(value) = text::value_from_frontmatter_line(line_text, column) {
@@ -126,7 +149,7 @@ fn definition_in_frontmatter(
             let target = collection_utils::parse_link_value(&value).unwrap_or(value);
             debug!(target = %target, "goto fm: parsed link target");
             if let Some(resolved) =
-                collection_utils::resolve_link_target(collection, &target, rel_path)
+                link_resolve::resolve_target(state, collection, &target, rel_path)
             {
                 return make_location_response(&resolved);
             }
@@ -136,6 +159,71 @@ fn definition_in_frontmatter(
     None
 }
 
+/// Locate the declaration of the field at `field_path` for each matched type (or
+/// every type when none matched), walking the `extends` chain to the ancestor
+/// that actually declares its top-level field. Nested paths point at the
+/// sub-field inside that declaration.
+fn field_definition_response(
+    state: &BackendState,
+    collection: &mdbase::Collection,
+    type_names: &[String],
+    field_path: &[String],
+) -> Option<GotoDefinitionResponse> {
+    let field_name = field_path.first()?.as_str();
+    let candidates: Vec<String> = if type_names.is_empty() {
+        let mut all: Vec<String> = collection.types.keys().cloned().collect();
+        all.sort();
+        all
+    } else {
+        type_names.to_vec()
+    };
+
+    let mut declaring: Vec<String> = Vec::new();
+    for type_name in &candidates {
+        if let Some(owner) =
+            collection_utils::find_declaring_type(collection, type_name, field_name)
+        {
+            if !declaring.contains(&owner) {
+                declaring.push(owner);
+            }
+        }
+    }
+
+    let mut locations = Vec::new();
+    for owner in &declaring {
+        let Some(path) = collection_utils::find_type_definition_path(collection, owner) else {
+            continue;
+        };
+        let Ok(target_uri) = Url::from_file_path(&path) else {
+            continue;
+        };
+        let type_text = state
+            .document_text(&target_uri)
+            .or_else(|| std::fs::read_to_string(&path).ok())
+            .unwrap_or_default();
+        let range = text::nested_field_declaration(&type_text, field_path)
+            .or_else(|| {
+                text::type_field_declarations(&type_text)
+                    .into_iter()
+                    .find(|decl| decl.name == field_name)
+            })
+            .map(|decl| {
+                Range::new(
+                    Position::new(decl.line as u32, decl.start_col as u32),
+                    Position::new(decl.line as u32, decl.end_col as u32),
+                )
+            })
+            .unwrap_or_default();
+        locations.push(Location::new(target_uri, range));
+    }
+
+    match locations.len() {
+        0 => None,
+        1 => Some(GotoDefinitionResponse::Scalar(locations.remove(0))),
+        _ => Some(GotoDefinitionResponse::Array(locations)),
+    }
+}
+
 /// Build a `GotoDefinitionResponse::Scalar` pointing to line 0 of the given path.
 fn make_location_response(path: &PathBuf) -> Option<GotoDefinitionResponse> {
     let target_uri = Url::from_file_path(path).ok()?;
@@ -16,6 +16,10 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Ho
     let line_text = text.lines().nth(line_idx).unwrap_or("").to_string();
     let column = position.character as usize;
 
+    if let Some(tag) = crate::tag_refs::tag_at_position(&text, position) {
+        return Some(crate::tag_refs::hover(state, &tag));
+    }
+
     if text::is_in_frontmatter(&text, line_idx) {
         let parsed = state
             .documents
@@ -34,11 +38,19 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Ho
 
         if let Some(field_name) = text::field_name_from_line(&line_text) {
             let colon_idx = line_text.find(':').unwrap_or(0);
+            let path = text::field_path_at(&text, line_idx)
+                .map(|p| p.segments())
+                .unwrap_or_else(|| vec![field_name.clone()]);
+            let path_field_def =
+                || crate::collection_utils::field_def_for_path(&collection, &type_names, &path);
             if column <= colon_idx {
-                if let Some(field_def) = field_def_for_types(&collection, &type_names, &field_name)
-                {
+                if let Some(field_def) = path_field_def() {
                     let mut contents = String::new();
-                    contents.push_str(&format!("**{}**: `{}`", field_name, field_def.field_type));
+                    contents.push_str(&format!(
+                        "**{}**: `{}`",
+                        path.join("."),
+                        field_def.field_type
+                    ));
                     if field_def.required {
                         contents.push_str("\n\nRequired");
                     }
@@ -84,8 +96,26 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Ho
                     }
                 }
 
-                if let Some(field_def) = field_def_for_types(&collection, &type_names, &field_name)
-                {
+                if let Some(field_def) = path_field_def() {
+                    if crate::dates::is_date_type(&field_def.field_type) {
+                        if let Some(contents) = crate::dates::scalar_value_range(&line_text)
+                            .and_then(|(s, e)| {
+                                crate::dates::hover_text(
+                                    &field_name,
+                                    &field_def.field_type,
+                                    &line_text[s..e],
+                                )
+                            })
+                        {
+                            return Some(Hover {
+                                contents: HoverContents::Markup(MarkupContent {
+                                    kind: MarkupKind::Markdown,
+                                    value: contents,
+                                }),
+                                range: None,
+                            });
+                        }
+                    }
                     if is_link_field(&field_def) {
                         if let Some(rel_path) = rel_path {
                             let resolved = collection.resolve_link(&serde_json::json!({
@@ -139,7 +169,8 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Ho
                 .ok()
                 .map(|r| r.to_string_lossy().to_string().replace('\\', "/"))
         });
-        if let Some(resolved) = crate::collection_utils::resolve_link_target(
+        if let Some(resolved) = crate::link_resolve::resolve_target(
+            state,
             &collection,
             &link.target,
             rel_path.as_deref(),
@@ -198,30 +229,6 @@ pub fn provide(state: &BackendState, uri: &Url, position: Position) -> Option<Ho
     None
 }
 
-fn field_def_for_types(
-    collection: &mdbase::Collection,
-    type_names: &[String],
-    field_name: &str,
-) -> Option<mdbase::types::schema::FieldDef> {
-    if type_names.is_empty() {
-        for type_def in collection.types.values() {
-            if let Some(def) = type_def.fields.get(field_name) {
-                return Some(def.clone());
-            }
-        }
-        None
-    } else {
-        for type_name in type_names {
-            if let Some(type_def) = collection.types.get(type_name) {
-                if let Some(def) = type_def.fields.get(field_name) {
-                    return Some(def.clone());
-                }
-            }
-        }
-        None
-    }
-}
-
 fn is_link_field(def: &mdbase::types::schema::FieldDef) -> bool {
     if def.field_type == "link" {
         return true;
@@ -0,0 +1,285 @@
+/// Link-related diagnostics that need the file index: aliases declared by
+/// more than one note, bare-name links matching several notes, and links to
+/// notes that don't exist.
+use tower_lsp::lsp_types::*;
+
+use crate::body_links::{self, BodyLink};
+use crate::collection_utils;
+use crate::link_resolve;
+use crate::link_style;
+use crate::state::BackendState;
+use crate::text;
+
+/// Compute link diagnostics for a note.
+pub(crate) fn compute(state: &BackendState, text: &str, rel_path: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = alias_collisions(state, text, rel_path);
+    diagnostics.extend(ambiguous_links(state, text, rel_path));
+    diagnostics.extend(unresolved_links(state, text, rel_path));
+    diagnostics
+}
+
+/// Warn on links to notes that don't exist.
+///
+/// Links are checked against the file index first, aliases included; only
+/// misses are confirmed against one listing of the collection on disk, since
+/// the index skips notes with broken frontmatter. Links to attachments (other
+/// file extensions) are ignored.
+fn unresolved_links(state: &BackendState, text: &str, rel_path: &str) -> Vec<Diagnostic> {
+    let Some(collection) = state.get_collection() else {
+        return Vec::new();
+    };
+    let links = body_links::find_body_links(text);
+    let config = state.config();
+    let misses: Vec<&BodyLink> = state.file_index.with_entries(|entries| {
+        let resolver = link_resolve::IndexResolver::new(
+            entries,
+            &collection.settings.extensions,
+            &config.link_tie_break,
+        );
+        links
+            .iter()
+            .filter(|link| !link.target.trim().is_empty())
+            .filter(|link| resolver.resolve(&link.target, Some(rel_path)).is_none())
+            .collect()
+    });
+
+    let misses: Vec<&BodyLink> = misses
+        .into_iter()
+        .filter(|link| !is_attachment(&collection, &link.target))
+        .collect();
+    if misses.is_empty() {
+        return Vec::new();
+    }
+
+    let on_disk: Vec<String> = collection_utils::scan_collection_files(&collection)
+        .iter()
+        .filter_map(|p| {
+            p.strip_prefix(&collection.root)
+                .ok()
+                .map(|r| r.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    let disk = collection_utils::PathResolver::new(
+        on_disk.iter().map(String::as_str),
+        &collection.settings.extensions,
+        &config.link_tie_break,
+    );
+    misses
+        .into_iter()
+        .filter(|link| disk.resolve(&link.target, Some(rel_path)).is_none())
+        .map(|link| {
+            let target = link.target.trim();
+            Diagnostic {
+                range: Range::new(
+                    Position::new(link.start_line as u32, link.start_col as u32),
+                    Position::new(link.end_line as u32, link.end_col as u32),
+                ),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("unresolved_link".to_string())),
+                source: Some("mdbase".to_string()),
+                message: format!("Unresolved link `{}`", target),
+                data: Some(serde_json::json!({ "target": target })),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// File types linked as attachments rather than notes.
+const ATTACHMENT_EXTENSIONS: &[&str] = &[
+    "png",
+    "jpg",
+    "jpeg",
+    "gif",
+    "svg",
+    "webp",
+    "bmp",
+    "avif",
+    "ico",
+    "tif",
+    "tiff",
+    "heic",
+    "pdf",
+    "mp3",
+    "wav",
+    "ogg",
+    "m4a",
+    "flac",
+    "mp4",
+    "webm",
+    "mov",
+    "mkv",
+    "avi",
+    "zip",
+    "csv",
+    "doc",
+    "docx",
+    "xls",
+    "xlsx",
+    "ppt",
+    "pptx",
+    "odt",
+    "ods",
+    "canvas",
+    "excalidraw",
+];
+
+/// Whether the link points at a non-note file such as an image or PDF.
+/// Names that merely contain a dot (`[[v1.2]]`) are still notes.
+fn is_attachment(collection: &mdbase::Collection, target: &str) -> bool {
+    attachment_extension(target).is_some_and(|ext| {
+        !collection
+            .settings
+            .extensions
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(ext))
+    })
+}
+
+/// The extension of `target` when it is a known attachment file type.
+fn attachment_extension(target: &str) -> Option<&str> {
+    let ext = std::path::Path::new(target.trim()).extension()?.to_str()?;
+    ATTACHMENT_EXTENSIONS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(ext))
+        .then_some(ext)
+}
+
+/// Warn on body links whose bare name matches the stem of several notes.
+///
+/// The diagnostic lists the candidates in tie-break order and carries, for
+/// each, the link rewritten to that note's path.
+fn ambiguous_links(state: &BackendState, text: &str, rel_path: &str) -> Vec<Diagnostic> {
+    let config = state.config();
+    let links = body_links::find_body_links(text);
+    state.file_index.with_entries(|entries| {
+        let paths: Vec<&str> = entries.iter().map(|e| e.rel_path.as_str()).collect();
+        let mut diagnostics = Vec::new();
+        for link in &links {
+            let target = link.target.trim();
+            if target.is_empty()
+                || target.contains('/')
+                || paths.contains(&target)
+                || paths.contains(&format!("{}.md", target).as_str())
+            {
+                continue;
+            }
+            let candidates = collection_utils::stem_matches(paths.iter().copied(), target);
+            if candidates.len() < 2 {
+                continue;
+            }
+            let candidates = collection_utils::order_stem_matches(
+                candidates,
+                Some(rel_path),
+                &config.link_tie_break,
+            );
+            let replacements: Vec<serde_json::Value> = candidates
+                .iter()
+                .map(|candidate| {
+                    serde_json::json!({
+                        "path": candidate,
+                        "newText": link_style::rewrite_link(&config, link, rel_path, candidate, &paths),
+                    })
+                })
+                .collect();
+            diagnostics.push(Diagnostic {
+                range: Range::new(
+                    Position::new(link.start_line as u32, link.start_col as u32),
+                    Position::new(link.end_line as u32, link.end_col as u32),
+                ),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("ambiguous_link".to_string())),
+                source: Some("mdbase".to_string()),
+                message: format!(
+                    "Ambiguous link `{}` matches {} notes: {} (resolves to {})",
+                    target,
+                    candidates.len(),
+                    candidates.join(", "),
+                    candidates[0]
+                ),
+                data: Some(serde_json::json!({
+                    "target": target,
+                    "candidates": replacements,
+                })),
+                ..Default::default()
+            });
+        }
+        diagnostics
+    })
+}
+
+/// Warn on each alias of this note that another note also declares.
+fn alias_collisions(state: &BackendState, text: &str, rel_path: &str) -> Vec<Diagnostic> {
+    let aliases_field = state.config().aliases_field;
+    let Some((start, end)) = text::frontmatter_bounds(text) else {
+        return Vec::new();
+    };
+
+    let mut diagnostics = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        if line_idx < start || line_idx > end {
+            continue;
+        }
+        let nested_key =
+            line.starts_with(char::is_whitespace) && !line.trim_start().starts_with('-');
+        if nested_key
+            || text::field_name_for_position(text, line_idx).as_deref() != Some(&aliases_field)
+        {
+            continue;
+        }
+        for (s, e) in text::value_tokens(line) {
+            let alias = &line[s..e];
+            let owners = state.file_index.alias_owners(alias, rel_path);
+            if owners.is_empty() {
+                continue;
+            }
+            diagnostics.push(Diagnostic {
+                range: Range::new(
+                    Position::new(line_idx as u32, s as u32),
+                    Position::new(line_idx as u32, e as u32),
+                ),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("alias_collision".to_string())),
+                source: Some("mdbase".to_string()),
+                message: format!(
+                    "Alias `{}` is also declared by {}",
+                    alias,
+                    owners.join(", ")
+                ),
+                data: Some(serde_json::json!({ "alias": alias, "notes": owners })),
+                ..Default::default()
+            });
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stem_matches_are_ordered_by_tie_break() {
+        use crate::config::LinkTieBreak;
+        let paths = ["z/deep/index.md", "b/index.md", "notes/index.md", "a.md"];
+        let candidates = collection_utils::stem_matches(paths.iter().copied(), "INDEX");
+        let ordered = collection_utils::order_stem_matches(
+            candidates,
+            Some("notes/today.md"),
+            &[LinkTieBreak::SameFolder, LinkTieBreak::ShortestPath],
+        );
+        assert_eq!(
+            ordered,
+            vec!["notes/index.md", "b/index.md", "z/deep/index.md"]
+        );
+    }
+
+    #[test]
+    fn only_known_file_types_are_attachments() {
+        assert_eq!(attachment_extension("diagram.PNG"), Some("PNG"));
+        assert_eq!(attachment_extension("docs/spec.pdf"), Some("pdf"));
+        assert_eq!(attachment_extension("v1.2"), None);
+        assert_eq!(attachment_extension("Dr. Who"), None);
+        assert_eq!(attachment_extension("notes.md"), None);
+    }
+}
@@ -0,0 +1,371 @@
+/// Quick fixes for unresolved links — "did you mean" rewrites to notes with a
+/// similar stem, title or alias, a "fix all" that repairs every link to the
+/// same broken target across the collection, and creating the missing note.
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::body_links::{self, BodyLink};
+use crate::collection_utils;
+use crate::file_index::FileEntry;
+use crate::fuzzy;
+use crate::link_style;
+use crate::state::BackendState;
+
+/// Most "did you mean" suggestions offered per link.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A note whose name is close to a broken link target.
+#[derive(Debug, Clone, PartialEq)]
+struct Suggestion {
+    rel_path: String,
+    /// The stem, title or alias that was closest.
+    name: String,
+    distance: usize,
+}
+
+/// Code actions for an `unresolved_link` diagnostic.
+pub(crate) fn unresolved_link_actions(
+    state: &BackendState,
+    collection: &Collection,
+    uri: &Url,
+    text: &str,
+    rel_path: &str,
+    diagnostic: &Diagnostic,
+) -> Vec<CodeActionOrCommand> {
+    if diagnostic.code != Some(NumberOrString::String("unresolved_link".to_string())) {
+        return Vec::new();
+    }
+    let start = diagnostic.range.start;
+    let Some(link) = body_links::body_link_at(text, start.line as usize, start.character as usize)
+    else {
+        return Vec::new();
+    };
+    let config = state.config();
+
+    let (suggestions, all_paths) = state.file_index.with_entries(|entries| {
+        let paths: Vec<String> = entries.iter().map(|e| e.rel_path.clone()).collect();
+        (suggestions(entries, &link.target), paths)
+    });
+    let paths: Vec<&str> = all_paths.iter().map(String::as_str).collect();
+
+    let mut actions = Vec::new();
+    for (idx, suggestion) in suggestions.iter().enumerate() {
+        let new_text =
+            link_style::rewrite_link(&config, &link, rel_path, &suggestion.rel_path, &paths);
+        let mut changes = HashMap::new();
+        changes.insert(
+            uri.clone(),
+            vec![TextEdit {
+                range: diagnostic.range,
+                new_text,
+            }],
+        );
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!(
+                "Did you mean '{}'? ({})",
+                suggestion.name, suggestion.rel_path
+            ),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                ..Default::default()
+            }),
+            is_preferred: Some(idx == 0),
+            ..Default::default()
+        }));
+    }
+
+    if let Some(best) = suggestions.first() {
+        let changes = fix_all_edits(state, collection, &link.target, &best.rel_path, &paths);
+        let count: usize = changes.values().map(Vec::len).sum();
+        if count > 1 {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!(
+                    "Fix all {} links to '{}' → '{}'",
+                    count,
+                    link.target.trim(),
+                    best.rel_path
+                ),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }));
+        }
+    }
+    actions.extend(create_note_actions(
+        collection, uri, text, rel_path, diagnostic, &link,
+    ));
+    actions
+}
+
+/// Actions creating the missing note through `mdbase.createFile`.
+///
+/// A link in a frontmatter field whose definition names a target type gets a
+/// single action for that type. Otherwise the best choice of
+/// `collection_utils::new_note_types` and an untyped note get actions, and
+/// the other types are offered through a picker (`typeChoices`).
+fn create_note_actions(
+    collection: &Collection,
+    uri: &Url,
+    text: &str,
+    rel_path: &str,
+    diagnostic: &Diagnostic,
+    link: &BodyLink,
+) -> Vec<CodeActionOrCommand> {
+    let target = link.target.trim();
+    let Some(path) = new_note_path(target, rel_path) else {
+        return Vec::new();
+    };
+    let name = note_name(&path);
+    let input = serde_json::json!({
+        "path": path,
+        "frontmatter": {},
+        "link": { "uri": uri, "range": diagnostic.range },
+    });
+    let action = |title: String, input: serde_json::Value| {
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.clone(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            command: Some(Command {
+                title,
+                command: "mdbase.createFile".to_string(),
+                arguments: Some(vec![input]),
+            }),
+            ..Default::default()
+        })
+    };
+    let typed = |type_name: &str| {
+        let type_def = collection.types.get(type_name)?;
+        let input = typed_note_input(collection, input.clone(), type_name)?;
+        Some(action(
+            format!("Create note '{}' as {}", name, type_def.name),
+            input,
+        ))
+    };
+
+    if let Some(target_type) = field_target_type(collection, text, rel_path, link) {
+        return typed(&target_type).into_iter().collect();
+    }
+    let mut type_names = collection_utils::new_note_types(collection, &path)
+        .into_iter()
+        .flatten();
+    let mut actions: Vec<CodeActionOrCommand> = type_names
+        .next()
+        .and_then(|best| typed(&best))
+        .into_iter()
+        .collect();
+    actions.push(action(format!("Create note '{}'", path), input.clone()));
+    let others: Vec<String> = type_names.collect();
+    if !others.is_empty() {
+        let mut input = input;
+        input["typeChoices"] = serde_json::json!(others);
+        actions.push(action(
+            format!("Create note '{}' as another type…", name),
+            input,
+        ));
+    }
+    actions
+}
+
+/// `input` for `mdbase.createFile` creating the note at its `path` as
+/// `type_name`: the note's name becomes its title when the type has one, and
+/// the type's `filename_pattern`, if any, chooses the path.
+pub(crate) fn typed_note_input(
+    collection: &Collection,
+    mut input: serde_json::Value,
+    type_name: &str,
+) -> Option<serde_json::Value> {
+    let type_def = collection.types.get(type_name)?;
+    let name = note_name(input.get("path")?.as_str()?);
+    input["type"] = serde_json::json!(type_name);
+    if type_def.fields.contains_key("title") {
+        input["frontmatter"]["title"] = serde_json::json!(name);
+    }
+    if crate::commands::find_filename_pattern(collection, type_name).is_some() {
+        input.as_object_mut()?.remove("path");
+    }
+    Some(input)
+}
+
+/// The file stem of a note path.
+fn note_name(path: &str) -> String {
+    path.rsplit('/')
+        .next()
+        .unwrap_or(path)
+        .trim_end_matches(".md")
+        .to_string()
+}
+
+/// The collection-relative path a link target names, with `.md` added.
+/// Source-relative targets are resolved against the linking note's folder.
+fn new_note_path(target: &str, source_rel: &str) -> Option<String> {
+    let path = if target.starts_with("./") || target.starts_with("../") {
+        let dir = source_rel.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+        collection_utils::normalize_path_segments(&format!("{}/{}", dir, target))
+    } else {
+        target.trim_start_matches('/').to_string()
+    };
+    if path.is_empty() || path == "." || path.starts_with("..") {
+        return None;
+    }
+    if path.ends_with(".md") {
+        Some(path)
+    } else {
+        Some(format!("{}.md", path))
+    }
+}
+
+/// The target type of the link field holding `link`, when the link sits in
+/// the frontmatter.
+fn field_target_type(
+    collection: &Collection,
+    text: &str,
+    rel_path: &str,
+    link: &BodyLink,
+) -> Option<String> {
+    if !crate::text::is_in_frontmatter(text, link.start_line) {
+        return None;
+    }
+    let field = crate::text::field_name_for_position(text, link.start_line)?;
+    let parsed = crate::text::parse_frontmatter(text);
+    let types = collection.determine_types_for_path(&parsed.json, Some(rel_path));
+    let def = collection_utils::field_def_for_path(collection, &types, &[field])?;
+    let target = crate::completions::link_target_type(&def)?.to_lowercase();
+    collection.types.contains_key(&target).then_some(target)
+}
+
+/// Notes closest to `target` by edit distance over stem, title and aliases.
+///
+/// Only names within a third of the target's length (at least one edit) are
+/// considered; ties go to the shorter path.
+fn suggestions(entries: &[FileEntry], target: &str) -> Vec<Suggestion> {
+    let target = target.trim();
+    let target = target.rsplit('/').next().unwrap_or(target);
+    let target = target.strip_suffix(".md").unwrap_or(target);
+    if target.is_empty() {
+        return Vec::new();
+    }
+    let max_distance = (target.chars().count() / 3).max(1);
+
+    let mut result: Vec<Suggestion> = entries
+        .iter()
+        .filter_map(|entry| {
+            let stem = std::path::Path::new(&entry.rel_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("");
+            std::iter::once(stem)
+                .chain(entry.title.as_deref())
+                .chain(entry.aliases.iter().map(String::as_str))
+                .map(|name| (fuzzy::edit_distance(target, name), name))
+                .min_by_key(|(distance, _)| *distance)
+                .filter(|(distance, _)| *distance <= max_distance)
+                .map(|(distance, name)| Suggestion {
+                    rel_path: entry.rel_path.clone(),
+                    name: name.to_string(),
+                    distance,
+                })
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then_with(|| a.rel_path.len().cmp(&b.rel_path.len()))
+            .then_with(|| a.rel_path.cmp(&b.rel_path))
+    });
+    result.truncate(MAX_SUGGESTIONS);
+    result
+}
+
+/// Edits rewriting every link whose target is exactly `broken` to point at
+/// `target_rel`, in every note of the collection.
+fn fix_all_edits(
+    state: &BackendState,
+    collection: &Collection,
+    broken: &str,
+    target_rel: &str,
+    paths: &[&str],
+) -> HashMap<Url, Vec<TextEdit>> {
+    let config = state.config();
+    let broken = broken.trim();
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for (file_uri, source_rel, text) in collection_utils::collection_texts(state, collection) {
+        let edits: Vec<TextEdit> = body_links::find_body_links(&text)
+            .into_iter()
+            .filter(|link: &BodyLink| link.target.trim() == broken)
+            .map(|link| TextEdit {
+                range: Range::new(
+                    Position::new(link.start_line as u32, link.start_col as u32),
+                    Position::new(link.end_line as u32, link.end_col as u32),
+                ),
+                new_text: link_style::rewrite_link(&config, &link, &source_rel, target_rel, paths),
+            })
+            .collect();
+        if !edits.is_empty() {
+            changes.insert(file_uri, edits);
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(rel_path: &str, title: Option<&str>, aliases: &[&str]) -> FileEntry {
+        FileEntry {
+            rel_path: rel_path.to_string(),
+            types: Vec::new(),
+            tags: Vec::new(),
+            display_name: title.map(str::to_string),
+            title: title.map(str::to_string),
+            id: None,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            links: Vec::new(),
+            field_values: Default::default(),
+        }
+    }
+
+    #[test]
+    fn suggests_close_stems_titles_and_aliases() {
+        let entries = vec![
+            entry("projects/project-plan.md", Some("Project Plan"), &[]),
+            entry("notes/js.md", None, &["JavaScript"]),
+            entry("notes/unrelated.md", Some("Groceries"), &[]),
+        ];
+        let found = suggestions(&entries, "Projcet Plan");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].rel_path, "projects/project-plan.md");
+        assert_eq!(found[0].name, "Project Plan");
+
+        let found = suggestions(&entries, "Javascrpt");
+        assert_eq!(found[0].rel_path, "notes/js.md");
+        assert!(suggestions(&entries, "zzz").is_empty());
+    }
+
+    #[test]
+    fn new_note_path_adds_extension_and_resolves_relative_targets() {
+        assert_eq!(
+            new_note_path("New Idea", "notes/a.md").as_deref(),
+            Some("New Idea.md")
+        );
+        assert_eq!(
+            new_note_path("./b", "notes/a.md").as_deref(),
+            Some("notes/b.md")
+        );
+        assert_eq!(
+            new_note_path("/ideas/c.md", "notes/a.md").as_deref(),
+            Some("ideas/c.md")
+        );
+        assert_eq!(new_note_path("../../x", "notes/a.md"), None);
+    }
+}
@@ -0,0 +1,238 @@
+/// Link completion ranking — fuzzy matches notes on title, aliases, stem and
+/// path, then boosts recently used notes, notes in the current note's link
+/// neighborhood, and notes in nearby folders.
+use std::collections::HashSet;
+
+use crate::file_index::{FileEntry, LinkGraph};
+use crate::fuzzy;
+use crate::link_resolve;
+
+/// Most link completion items returned per request.
+pub(crate) const MAX_LINK_COMPLETIONS: usize = 50;
+
+/// Per-request signals used to rank link targets.
+#[derive(Debug, Default)]
+pub(crate) struct RankContext {
+    /// The note being edited.
+    pub source: Option<String>,
+    /// Recently opened or edited notes, most recent first.
+    pub recent: Vec<String>,
+    /// Notes the current note already links to.
+    pub linked: HashSet<String>,
+    /// Notes linked from the current note's neighbors (notes it links to or
+    /// that link to it).
+    pub neighbor_linked: HashSet<String>,
+}
+
+impl RankContext {
+    /// Build the context from the current note's text, resolving its links
+    /// through `index`, and the cached link `graph` of the other notes.
+    pub fn build(
+        graph: &LinkGraph,
+        index: &link_resolve::IndexResolver,
+        source: Option<&str>,
+        source_text: &str,
+        recent: Vec<String>,
+    ) -> Self {
+        let linked: HashSet<String> = crate::body_links::find_body_links(source_text)
+            .into_iter()
+            .filter_map(|link| index.resolve(&link.target, source).map(str::to_string))
+            .collect();
+
+        let mut neighbor_linked = HashSet::new();
+        for (rel_path, targets) in graph {
+            if Some(rel_path.as_str()) == source {
+                continue;
+            }
+            let is_neighbor =
+                linked.contains(rel_path) || source.is_some_and(|s| targets.contains(s));
+            if is_neighbor {
+                neighbor_linked.extend(targets.iter().cloned());
+            }
+        }
+        if let Some(source) = source {
+            neighbor_linked.remove(source);
+        }
+
+        Self {
+            source: source.map(str::to_string),
+            recent,
+            linked,
+            neighbor_linked,
+        }
+    }
+}
+
+/// A link target that matched the query.
+#[derive(Debug)]
+pub(crate) struct RankedTarget<'a> {
+    pub entry: &'a FileEntry,
+    pub score: i64,
+    /// The title, alias, stem or path the query matched, for client-side
+    /// filtering.
+    pub matched: String,
+}
+
+/// Rank `entries` against `query`, best first, keeping at most `limit`.
+///
+/// Returns the ranked targets and whether more matches were cut off.
+pub(crate) fn rank<'a>(
+    entries: &'a [FileEntry],
+    query: &str,
+    ctx: &RankContext,
+    limit: usize,
+) -> (Vec<RankedTarget<'a>>, bool) {
+    let mut ranked: Vec<RankedTarget<'a>> = entries
+        .iter()
+        .filter(|e| ctx.source.as_deref() != Some(e.rel_path.as_str()))
+        .filter_map(|entry| {
+            let (score, matched) = best_match(entry, query)?;
+            Some(RankedTarget {
+                entry,
+                score: score + context_bonus(entry, ctx),
+                matched,
+            })
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.entry.rel_path.cmp(&b.entry.rel_path))
+    });
+    let truncated = ranked.len() > limit;
+    ranked.truncate(limit);
+    (ranked, truncated)
+}
+
+/// The best fuzzy score over the note's names. Path matches are weighted
+/// down since folder names match many notes at once.
+fn best_match(entry: &FileEntry, query: &str) -> Option<(i64, String)> {
+    let path = entry
+        .rel_path
+        .strip_suffix(".md")
+        .unwrap_or(&entry.rel_path);
+    let stem = path.rsplit('/').next().unwrap_or(path);
+    let names = entry
+        .title
+        .iter()
+        .chain(entry.display_name.iter())
+        .chain(entry.aliases.iter())
+        .map(String::as_str)
+        .chain(std::iter::once(stem));
+
+    let mut best: Option<(i64, String)> = None;
+    for name in names {
+        if let Some(score) = fuzzy::fuzzy_score(query, name) {
+            if best.as_ref().is_none_or(|(b, _)| score > *b) {
+                best = Some((score, name.to_string()));
+            }
+        }
+    }
+    if let Some(score) = fuzzy::fuzzy_score(query, path) {
+        let score = score * 2 / 3;
+        if best.as_ref().is_none_or(|(b, _)| score > *b) {
+            best = Some((score, path.to_string()));
+        }
+    }
+    best
+}
+
+fn context_bonus(entry: &FileEntry, ctx: &RankContext) -> i64 {
+    let mut bonus = 0;
+    if let Some(idx) = ctx.recent.iter().position(|r| *r == entry.rel_path) {
+        bonus += 20 - (idx as i64).min(20);
+    }
+    if ctx.linked.contains(&entry.rel_path) {
+        bonus += 15;
+    } else if ctx.neighbor_linked.contains(&entry.rel_path) {
+        bonus += 8;
+    }
+    if let Some(source) = &ctx.source {
+        bonus += folder_proximity(source, &entry.rel_path);
+    }
+    bonus
+}
+
+/// Bonus for notes in the same folder as the source, or sharing ancestors.
+fn folder_proximity(source: &str, target: &str) -> i64 {
+    let source_dirs: Vec<&str> = source.split('/').collect();
+    let target_dirs: Vec<&str> = target.split('/').collect();
+    let source_dirs = &source_dirs[..source_dirs.len() - 1];
+    let target_dirs = &target_dirs[..target_dirs.len() - 1];
+    if source_dirs == target_dirs {
+        return 10;
+    }
+    let common = source_dirs
+        .iter()
+        .zip(target_dirs)
+        .take_while(|(a, b)| a == b)
+        .count();
+    (common as i64 * 3).min(9)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_index;
+
+    fn entry(rel_path: &str, title: Option<&str>, links: &[&str]) -> FileEntry {
+        FileEntry {
+            rel_path: rel_path.to_string(),
+            types: Vec::new(),
+            tags: Vec::new(),
+            display_name: title.map(str::to_string),
+            title: title.map(str::to_string),
+            id: None,
+            aliases: Vec::new(),
+            links: links.iter().map(|l| l.to_string()).collect(),
+            field_values: Default::default(),
+        }
+    }
+
+    #[test]
+    fn ranks_title_matches_and_neighbors_first() {
+        let entries = vec![
+            entry("notes/current.md", None, &[]),
+            entry("notes/alpha.md", Some("Project Alpha"), &["beta"]),
+            entry("archive/beta.md", Some("Project Beta"), &[]),
+            entry("archive/gamma.md", Some("Gamma"), &[]),
+        ];
+        let index = link_resolve::IndexResolver::new(&entries, &[], &[]);
+        let graph: LinkGraph = entries
+            .iter()
+            .map(|e| (e.rel_path.clone(), file_index::resolved_links(e, &index)))
+            .collect();
+        let ctx = RankContext::build(
+            &graph,
+            &index,
+            Some("notes/current.md"),
+            "See [[alpha]].",
+            Vec::new(),
+        );
+        assert!(ctx.linked.contains("notes/alpha.md"));
+        assert!(ctx.neighbor_linked.contains("archive/beta.md"));
+
+        let (ranked, truncated) = rank(&entries, "proj", &ctx, 10);
+        let paths: Vec<&str> = ranked.iter().map(|r| r.entry.rel_path.as_str()).collect();
+        assert_eq!(paths, vec!["notes/alpha.md", "archive/beta.md"]);
+        assert_eq!(ranked[0].matched, "Project Alpha");
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn recent_notes_break_ties_and_results_are_capped() {
+        let entries = vec![
+            entry("a/one.md", None, &[]),
+            entry("b/two.md", None, &[]),
+            entry("c/three.md", None, &[]),
+        ];
+        let ctx = RankContext {
+            recent: vec!["c/three.md".to_string()],
+            ..Default::default()
+        };
+        let (ranked, truncated) = rank(&entries, "", &ctx, 2);
+        assert_eq!(ranked[0].entry.rel_path, "c/three.md");
+        assert_eq!(ranked.len(), 2);
+        assert!(truncated);
+    }
+}
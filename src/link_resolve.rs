@@ -1,4 +1,5 @@
 /// Resolve body link targets to file paths within the collection.
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use tower_lsp::lsp_types::Url;
@@ -6,15 +7,20 @@ use tracing::debug;
 
 use crate::body_links::BodyLink;
 use crate::collection_utils;
+use crate::config::LinkTieBreak;
+use crate::file_index::FileEntry;
+use crate::state::BackendState;
 
 /// Resolve a `BodyLink` target to a file `Url`.
 ///
-/// Uses the existing `resolve_link_target` from `collection_utils`, which handles:
+/// Uses `resolve_target`, which handles:
 /// - Root-relative paths (containing `/`)
 /// - Source-relative paths (`./`, `../`)
 /// - Bare names (stem matching across collection)
 /// - Extension inference (`.md` appended)
+/// - Note aliases
 pub(crate) fn resolve_body_link(
+    state: &BackendState,
     collection: &mdbase::Collection,
     source_uri: &Url,
     link: &BodyLink,
@@ -31,11 +37,154 @@ pub(crate) fn resolve_body_link(
         "link_resolve: resolving body link"
     );
 
-    let resolved: PathBuf = collection_utils::resolve_link_target(
-        collection,
-        &link.target,
-        source_rel_path.as_deref(),
-    )?;
+    let resolved: PathBuf =
+        resolve_target(state, collection, &link.target, source_rel_path.as_deref())?;
 
     Url::from_file_path(&resolved).ok()
 }
+
+/// Resolve a link target by path or stem (see
+/// `collection_utils::resolve_link_target`), falling back to note aliases
+/// from the file index for bare names.
+pub(crate) fn resolve_target(
+    state: &BackendState,
+    collection: &mdbase::Collection,
+    target: &str,
+    source_rel_path: Option<&str>,
+) -> Option<PathBuf> {
+    if let Some(resolved) = collection_utils::resolve_link_target(
+        collection,
+        target,
+        source_rel_path,
+        &state.config().link_tie_break,
+    ) {
+        return Some(resolved);
+    }
+    let name = target.split('#').next().unwrap_or(target).trim();
+    if name.is_empty() || name.contains('/') {
+        return None;
+    }
+    let rel_path =
+        state
+            .file_index
+            .resolve_alias(name, source_rel_path, &state.config().link_tie_break)?;
+    debug!(alias = %name, matched = %rel_path, "link_resolve: alias match");
+    Some(collection.root.join(rel_path))
+}
+
+/// In-memory link resolution over the file index, following the same steps
+/// as `resolve_target` without touching the disk. Shared stems and aliases
+/// are both ordered by the tie-break criteria.
+pub(crate) struct IndexResolver {
+    paths: collection_utils::PathResolver,
+    aliases: HashMap<String, Vec<String>>,
+    tie_break: Vec<LinkTieBreak>,
+}
+
+impl IndexResolver {
+    pub(crate) fn new(
+        entries: &[FileEntry],
+        extensions: &[String],
+        tie_break: &[LinkTieBreak],
+    ) -> Self {
+        let paths = entries.iter().map(|e| e.rel_path.as_str());
+        Self::with_paths(paths, entries, extensions, tie_break)
+    }
+
+    /// A resolver for one request that resolves many links: the collection
+    /// is listed on disk once, so notes the index skips still resolve, and
+    /// aliases come from the index.
+    pub(crate) fn for_request(state: &BackendState, collection: &mdbase::Collection) -> Self {
+        let paths = collection_utils::scan_collection_files(collection)
+            .into_iter()
+            .filter_map(|p| {
+                p.strip_prefix(&collection.root)
+                    .ok()
+                    .map(|r| r.to_string_lossy().replace('\\', "/"))
+            });
+        let tie_break = state.config().link_tie_break;
+        state.file_index.with_entries(|entries| {
+            Self::with_paths(paths, entries, &collection.settings.extensions, &tie_break)
+        })
+    }
+
+    fn with_paths(
+        paths: impl IntoIterator<Item = impl Into<String>>,
+        entries: &[FileEntry],
+        extensions: &[String],
+        tie_break: &[LinkTieBreak],
+    ) -> Self {
+        let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+        for entry in entries {
+            for alias in &entry.aliases {
+                aliases
+                    .entry(alias.to_lowercase())
+                    .or_default()
+                    .push(entry.rel_path.clone());
+            }
+        }
+        Self {
+            paths: collection_utils::PathResolver::new(paths, extensions, tie_break),
+            aliases,
+            tie_break: tie_break.to_vec(),
+        }
+    }
+
+    pub(crate) fn resolve(&self, target: &str, source: Option<&str>) -> Option<&str> {
+        if let Some(path) = self.paths.resolve(target, source) {
+            return Some(path);
+        }
+        let name = target.split('#').next().unwrap_or(target).trim();
+        if name.contains('/') {
+            return None;
+        }
+        let owners = self.aliases.get(&name.to_lowercase())?;
+        let ordered = collection_utils::order_stem_matches(owners.clone(), source, &self.tie_break);
+        let first = ordered.first()?;
+        owners.iter().find(|rel| *rel == first).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(rel_path: &str, aliases: &[&str]) -> FileEntry {
+        FileEntry {
+            rel_path: rel_path.to_string(),
+            types: Vec::new(),
+            tags: Vec::new(),
+            display_name: None,
+            title: None,
+            id: None,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            links: Vec::new(),
+            field_values: Default::default(),
+        }
+    }
+
+    #[test]
+    fn index_resolver_follows_tie_break_and_extensions() {
+        let entries = vec![
+            entry("archive/index.md", &[]),
+            entry("notes/index.md", &["Home"]),
+            entry("notes/todo.txt", &[]),
+        ];
+        let tie_break = [LinkTieBreak::SameFolder, LinkTieBreak::ShortestPath];
+        let resolver = IndexResolver::new(&entries, &["txt".to_string()], &tie_break);
+        assert_eq!(
+            resolver.resolve("index", Some("notes/a.md")),
+            Some("notes/index.md")
+        );
+        assert_eq!(
+            resolver.resolve("index", Some("archive/b.md")),
+            Some("archive/index.md")
+        );
+        assert_eq!(resolver.resolve("notes/todo", None), Some("notes/todo.txt"));
+        assert_eq!(resolver.resolve("home#Intro", None), Some("notes/index.md"));
+        assert_eq!(
+            resolver.resolve("./index", Some("notes/a.md")),
+            Some("notes/index.md")
+        );
+    }
+}
@@ -0,0 +1,177 @@
+/// Link style — how inserted and rewritten links spell their target path.
+use tower_lsp::lsp_types::Range;
+
+use crate::body_links::{BodyLink, LinkFormat};
+use crate::collection_utils;
+use crate::config::{LinkStyle, ServerConfig};
+use crate::references::{self, FoundRef};
+
+/// The target text for a link from `source_rel` to `target_rel` (both
+/// collection-relative), following the configured style.
+///
+/// Without a configured style wikilinks use the collection-absolute path and
+/// markdown links the source-relative one; without a configured extension
+/// setting only markdown links keep `.md`. `paths` lists every note, for
+/// checking that a bare stem is unique.
+pub(crate) fn link_target(
+    config: &ServerConfig,
+    format: LinkFormat,
+    source_rel: Option<&str>,
+    target_rel: &str,
+    paths: &[&str],
+) -> String {
+    let style = config.link_style.unwrap_or(match format {
+        LinkFormat::Wikilink => LinkStyle::Absolute,
+        LinkFormat::Markdown => LinkStyle::Relative,
+    });
+    let with_extension = config
+        .link_extension
+        .unwrap_or(format == LinkFormat::Markdown);
+
+    let path = match (style, source_rel) {
+        (LinkStyle::ShortestUnique, _) if is_unique_stem(target_rel, paths) => target_rel
+            .rsplit('/')
+            .next()
+            .unwrap_or(target_rel)
+            .to_string(),
+        (LinkStyle::Relative, Some(source)) => {
+            collection_utils::relative_path_from(source, target_rel)
+        }
+        _ => target_rel.to_string(),
+    };
+    if with_extension {
+        path
+    } else {
+        path.strip_suffix(".md").map(str::to_string).unwrap_or(path)
+    }
+}
+
+/// The full text of `link` rewritten to point at `target_rel`, keeping its
+/// format, alias and anchor.
+pub(crate) fn rewrite_link(
+    config: &ServerConfig,
+    link: &BodyLink,
+    source_rel: &str,
+    target_rel: &str,
+    paths: &[&str],
+) -> String {
+    let new_target = link_target(config, link.format, Some(source_rel), target_rel, paths);
+    let found = FoundRef {
+        range: Range::default(),
+        format: link.format.into(),
+        alias: link.alias.clone(),
+        anchor: link.anchor.clone(),
+    };
+    references::replacement_for_ref(&found, &new_target)
+}
+
+/// Whether no other note shares the file stem of `target_rel`.
+fn is_unique_stem(target_rel: &str, paths: &[&str]) -> bool {
+    let stem = std::path::Path::new(target_rel)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+    collection_utils::stem_matches(paths.iter().copied(), stem)
+        .iter()
+        .all(|p| p == target_rel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(style: Option<LinkStyle>, extension: Option<bool>) -> ServerConfig {
+        ServerConfig {
+            link_style: style,
+            link_extension: extension,
+            ..Default::default()
+        }
+    }
+
+    const PATHS: &[&str] = &[
+        "notes/a.md",
+        "notes/index.md",
+        "projects/index.md",
+        "projects/plan.md",
+    ];
+
+    #[test]
+    fn defaults_follow_link_format() {
+        let cfg = config(None, None);
+        let wiki = link_target(
+            &cfg,
+            LinkFormat::Wikilink,
+            Some("notes/a.md"),
+            "projects/plan.md",
+            PATHS,
+        );
+        let md = link_target(
+            &cfg,
+            LinkFormat::Markdown,
+            Some("notes/a.md"),
+            "projects/plan.md",
+            PATHS,
+        );
+        assert_eq!(wiki, "projects/plan");
+        assert_eq!(md, "../projects/plan.md");
+    }
+
+    #[test]
+    fn shortest_unique_falls_back_to_absolute() {
+        let cfg = config(Some(LinkStyle::ShortestUnique), Some(false));
+        let unique = link_target(&cfg, LinkFormat::Wikilink, None, "projects/plan.md", PATHS);
+        let shared = link_target(&cfg, LinkFormat::Wikilink, None, "projects/index.md", PATHS);
+        assert_eq!(unique, "plan");
+        assert_eq!(shared, "projects/index");
+    }
+
+    #[test]
+    fn rewrite_link_keeps_alias_and_anchor() {
+        let links =
+            crate::body_links::find_body_links("See [[index#Intro|Home]] and [Docs](index.md).");
+        let cfg = config(None, None);
+        let paths = ["notes/index.md", "projects/index.md"];
+        let rewrite = |link| rewrite_link(&cfg, link, "notes/a.md", "projects/index.md", &paths);
+        assert_eq!(rewrite(&links[0]), "[[projects/index#Intro|Home]]");
+        assert_eq!(rewrite(&links[1]), "[Docs](../projects/index.md)");
+    }
+
+    #[test]
+    fn shortest_unique_with_extension_resolves_back_to_the_note() {
+        let cfg = config(Some(LinkStyle::ShortestUnique), Some(true));
+        let resolver =
+            collection_utils::PathResolver::new(PATHS.iter().copied(), &[], &cfg.link_tie_break);
+        let links = crate::body_links::find_body_links("[[x]] and [y](x.md)");
+        for link in &links {
+            let text = rewrite_link(&cfg, link, "notes/a.md", "projects/plan.md", PATHS);
+            let rewritten = &crate::body_links::find_body_links(&text)[0];
+            assert_eq!(rewritten.target, "plan.md");
+            assert_eq!(
+                resolver.resolve(&rewritten.target, Some("notes/a.md")),
+                Some("projects/plan.md"),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn relative_targets_resolve_back_to_the_note() {
+        let cfg = config(Some(LinkStyle::Relative), None);
+        let paths = ["notes/a.md", "notes/new.md", "notes/sub/new.md", "new.md"];
+        let resolver = collection_utils::PathResolver::new(paths, &[], &cfg.link_tie_break);
+        let links = crate::body_links::find_body_links("[[x]] and [y](x.md)");
+        for target in ["notes/new.md", "notes/sub/new.md"] {
+            for link in &links {
+                let text = rewrite_link(&cfg, link, "notes/a.md", target, &paths);
+                let rewritten = &crate::body_links::find_body_links(&text)[0];
+                assert_eq!(
+                    resolver.resolve(&rewritten.target, Some("notes/a.md")),
+                    Some(target),
+                    "{}",
+                    text
+                );
+            }
+        }
+    }
+}
@@ -2,21 +2,38 @@ use tower_lsp::{LspService, Server};
 use tracing_subscriber::EnvFilter;
 
 mod body_links;
+mod call_hierarchy;
 mod code_actions;
 mod collection_utils;
 mod commands;
 mod completions;
+mod config;
+mod dates;
 mod diagnostics;
 mod document_links;
+mod enum_values;
+mod extract_note;
+mod field_refs;
 mod file_index;
+mod fuzzy;
 mod goto;
 mod hover;
+mod link_diagnostics;
+mod link_fixes;
+mod link_rank;
 mod link_resolve;
+mod link_style;
+mod merge_notes;
 mod references;
 mod server;
+mod skeleton;
+mod split_note;
 mod state;
 mod symbols;
+mod tag_refs;
 mod text;
+mod type_hierarchy;
+mod type_refs;
 
 #[tokio::main]
 async fn main() {
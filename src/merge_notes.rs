@@ -0,0 +1,467 @@
+/// Merge notes — folds a duplicate note into another one.
+///
+/// The destination keeps its own scalar values, gains the fields it lacks and
+/// the union of list fields, and gets the source body appended under a
+/// heading. Links to the source are redirected and the source is deleted, all
+/// in one `WorkspaceEdit` that asks the editor for confirmation.
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::collection_utils;
+use crate::config::ServerConfig;
+use crate::link_resolve;
+use crate::references::{self, RefFormat};
+use crate::skeleton;
+use crate::state::BackendState;
+use crate::text;
+
+/// Change annotation shared by every part of the merge edit.
+const MERGE_ANNOTATION: &str = "mdbase.mergeNotes";
+
+/// A source field whose value differs from the destination's scalar value.
+/// The destination value is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Conflict {
+    pub field: String,
+    pub destination: serde_json::Value,
+    pub source: serde_json::Value,
+}
+
+/// A change to one destination frontmatter field.
+#[derive(Debug, Clone, PartialEq)]
+enum FieldChange {
+    /// The destination lacks the field; take the source value.
+    Added(String, serde_json::Value),
+    /// A list field gains the source items it does not have yet.
+    Extended(String, Vec<serde_json::Value>),
+}
+
+/// The edit merging the note at `source` into `destination`, and the
+/// conflicting fields, or `None` when either note cannot be read.
+pub(crate) fn merge_edit(
+    state: &BackendState,
+    collection: &Collection,
+    source: &Url,
+    destination: &Url,
+) -> Option<(WorkspaceEdit, Vec<Conflict>)> {
+    let source_rel = collection_utils::rel_path_from_uri(collection, source)?;
+    let dest_rel = collection_utils::rel_path_from_uri(collection, destination)?;
+    if source_rel == dest_rel {
+        return None;
+    }
+    let read = |uri: &Url, rel: &str| {
+        state
+            .document_text(uri)
+            .or_else(|| std::fs::read_to_string(collection.root.join(rel)).ok())
+    };
+    let source_text = read(source, &source_rel)?;
+    let dest_text = read(destination, &dest_rel)?;
+    let source_fm = text::parse_frontmatter(&source_text).json;
+    let dest_fm = text::parse_frontmatter(&dest_text).json;
+    let dest_types = collection.determine_types_for_path(&dest_fm, Some(&dest_rel));
+
+    let dest_fields = dest_fm.as_object().cloned().unwrap_or_default();
+    let (changes, conflicts) = merge_frontmatter(
+        source_fm.as_object().cloned().unwrap_or_default(),
+        dest_fields.clone(),
+        |field| collection_utils::is_type_key(collection, field),
+        |field| {
+            collection_utils::field_def_for_path(collection, &dest_types, &[field.to_string()])
+                .is_some_and(|def| def.field_type == "list")
+        },
+    );
+
+    let config = state.config();
+    let existing = state.file_index.with_entries(|entries| {
+        entries
+            .iter()
+            .map(|e| e.rel_path.clone())
+            .collect::<Vec<_>>()
+    });
+    // The source is going away, so it doesn't make the destination's stem
+    // ambiguous.
+    let paths: Vec<&str> = existing
+        .iter()
+        .map(String::as_str)
+        .filter(|p| *p != source_rel)
+        .collect();
+
+    let resolver = link_resolve::IndexResolver::for_request(state, collection);
+
+    // Destination: frontmatter fields, redirected links, appended body.
+    let (mut dest_edits, replaced_lines) = frontmatter_edits(&dest_text, &dest_fields, &changes);
+    for found in references::find_references_in_text(&resolver, &dest_text, &dest_rel, &source_rel)
+    {
+        let line = found.range.start.line as usize;
+        if replaced_lines
+            .iter()
+            .any(|(start, end)| (*start..*end).contains(&line))
+        {
+            continue;
+        }
+        dest_edits.push(TextEdit {
+            range: found.range,
+            new_text: references::retarget_ref(&config, &found, &dest_rel, &dest_rel, &paths),
+        });
+    }
+    let heading = source_fm
+        .get("title")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            source_rel
+                .rsplit('/')
+                .next()
+                .unwrap_or(&source_rel)
+                .trim_end_matches(".md")
+                .to_string()
+        });
+    let body = moved_body(
+        &resolver,
+        &config,
+        &source_text,
+        &source_rel,
+        &dest_rel,
+        &paths,
+    );
+    dest_edits.push(append_edit(&dest_text, &heading, &body));
+
+    let mut operations = vec![collection_utils::annotated_text_document_edit(
+        destination.clone(),
+        dest_edits,
+        MERGE_ANNOTATION,
+    )];
+
+    // Every other note: redirect links to the source.
+    for (file_uri, rel_path, file_text) in collection_utils::collection_texts(state, collection) {
+        if rel_path == source_rel || rel_path == dest_rel {
+            continue;
+        }
+        let edits: Vec<TextEdit> =
+            references::find_references_in_text(&resolver, &file_text, &rel_path, &source_rel)
+                .iter()
+                .map(|found| TextEdit {
+                    range: found.range,
+                    new_text: references::retarget_ref(
+                        &config, found, &rel_path, &dest_rel, &paths,
+                    ),
+                })
+                .collect();
+        if !edits.is_empty() {
+            operations.push(collection_utils::annotated_text_document_edit(
+                file_uri,
+                edits,
+                MERGE_ANNOTATION,
+            ));
+        }
+    }
+
+    operations.push(DocumentChangeOperation::Op(ResourceOp::Delete(
+        DeleteFile {
+            uri: source.clone(),
+            options: Some(DeleteFileOptions {
+                recursive: None,
+                ignore_if_not_exists: Some(true),
+                annotation_id: Some(MERGE_ANNOTATION.to_string()),
+            }),
+        },
+    )));
+
+    let mut annotations = HashMap::new();
+    annotations.insert(
+        MERGE_ANNOTATION.to_string(),
+        ChangeAnnotation {
+            label: format!("Merge '{}' into '{}'", source_rel, dest_rel),
+            needs_confirmation: Some(true),
+            description: (!conflicts.is_empty()).then(|| {
+                let fields: Vec<&str> = conflicts.iter().map(|c| c.field.as_str()).collect();
+                format!("Kept destination values for: {}", fields.join(", "))
+            }),
+        },
+    );
+    let edit = WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: Some(annotations),
+        ..Default::default()
+    };
+    Some((edit, conflicts))
+}
+
+/// Merge the source frontmatter into the destination's.
+///
+/// Fields the destination lacks are added; list fields (by definition, or
+/// when either value is a list) gain the missing source items; differing
+/// scalars keep the destination value and are reported. Type keys are never
+/// changed.
+fn merge_frontmatter(
+    source: serde_json::Map<String, serde_json::Value>,
+    dest: serde_json::Map<String, serde_json::Value>,
+    is_type_key: impl Fn(&str) -> bool,
+    is_list_field: impl Fn(&str) -> bool,
+) -> (Vec<FieldChange>, Vec<Conflict>) {
+    let mut changes = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut keys: Vec<&String> = source.keys().collect();
+    keys.sort();
+    for key in keys {
+        let source_value = &source[key];
+        let Some(dest_value) = dest.get(key) else {
+            if !is_type_key(key) && !source_value.is_null() {
+                changes.push(FieldChange::Added(key.clone(), source_value.clone()));
+            }
+            continue;
+        };
+        if dest_value == source_value || source_value.is_null() {
+            continue;
+        }
+        let is_list = !is_type_key(key)
+            && (is_list_field(key) || dest_value.is_array() || source_value.is_array());
+        if !is_list {
+            conflicts.push(Conflict {
+                field: key.clone(),
+                destination: dest_value.clone(),
+                source: source_value.clone(),
+            });
+            continue;
+        }
+        let as_items = |value: &serde_json::Value| match value {
+            serde_json::Value::Array(items) => items.clone(),
+            serde_json::Value::Null => Vec::new(),
+            other => vec![other.clone()],
+        };
+        let dest_items = as_items(dest_value);
+        let mut new_items: Vec<serde_json::Value> = Vec::new();
+        for item in as_items(source_value) {
+            if !dest_items.contains(&item) && !new_items.contains(&item) {
+                new_items.push(item);
+            }
+        }
+        if !new_items.is_empty() {
+            changes.push(FieldChange::Extended(key.clone(), new_items));
+        }
+    }
+    (changes, conflicts)
+}
+
+/// Text edits applying `changes` to the destination's frontmatter (`text`,
+/// parsed as `fields`), and the line ranges of fields they replace wholesale.
+fn frontmatter_edits(
+    text: &str,
+    fields: &serde_json::Map<String, serde_json::Value>,
+    changes: &[FieldChange],
+) -> (Vec<TextEdit>, Vec<(usize, usize)>) {
+    let mut edits = Vec::new();
+    let mut replaced = Vec::new();
+    if changes.is_empty() {
+        return (edits, replaced);
+    }
+    let lines: Vec<&str> = text.lines().collect();
+    let insert = |line: usize, new_text: String| TextEdit {
+        range: Range::new(Position::new(line as u32, 0), Position::new(line as u32, 0)),
+        new_text,
+    };
+
+    let Some((_, end)) = text::frontmatter_bounds(text) else {
+        // No frontmatter yet (or an empty `---` pair): write a fresh block.
+        let mut block = String::new();
+        for change in changes {
+            if let FieldChange::Added(key, value) = change {
+                block.push_str(&format!("{}: {}\n", key, skeleton::yaml_scalar(value)));
+            }
+        }
+        let empty_pair = lines.first().is_some_and(|l| l.trim_end() == "---")
+            && lines.get(1).is_some_and(|l| l.trim_end() == "---");
+        if empty_pair {
+            edits.push(insert(1, block));
+        } else {
+            edits.push(insert(0, format!("---\n{}---\n", block)));
+        }
+        return (edits, replaced);
+    };
+
+    for change in changes {
+        match change {
+            FieldChange::Added(key, value) => {
+                edits.push(insert(
+                    end + 1,
+                    format!("{}: {}\n", key, skeleton::yaml_scalar(value)),
+                ));
+            }
+            FieldChange::Extended(key, new_items) => {
+                let Some((start, field_end)) = text::top_level_field_lines(text, key) else {
+                    continue;
+                };
+                let block = &lines[start + 1..field_end];
+                let inline_value = lines[start].split_once(':').map_or("", |(_, v)| v.trim());
+                let block_list = inline_value.is_empty()
+                    && !block.is_empty()
+                    && block.iter().all(|l| l.trim_start().starts_with("- "));
+                if block_list {
+                    let last = block[block.len() - 1];
+                    let indent = &last[..last.len() - last.trim_start().len()];
+                    let new_text: String = new_items
+                        .iter()
+                        .map(|item| format!("{}- {}\n", indent, skeleton::yaml_scalar(item)))
+                        .collect();
+                    edits.push(insert(field_end, new_text));
+                } else {
+                    let current = fields.get(key).cloned().unwrap_or_default();
+                    let mut items = match current {
+                        serde_json::Value::Array(items) => items,
+                        serde_json::Value::Null => Vec::new(),
+                        other => vec![other],
+                    };
+                    items.extend(new_items.iter().cloned());
+                    edits.push(TextEdit {
+                        range: Range::new(
+                            Position::new(start as u32, 0),
+                            Position::new(field_end as u32, 0),
+                        ),
+                        new_text: format!(
+                            "{}: {}\n",
+                            key,
+                            skeleton::yaml_scalar(&serde_json::Value::Array(items))
+                        ),
+                    });
+                    replaced.push((start, field_end));
+                }
+            }
+        }
+    }
+    (edits, replaced)
+}
+
+/// The source body as it reads from the destination: links to the source
+/// point at the destination, and when the notes live in different folders
+/// every resolved link is restyled from the destination's location.
+fn moved_body(
+    resolver: &link_resolve::IndexResolver,
+    config: &ServerConfig,
+    source_text: &str,
+    source_rel: &str,
+    dest_rel: &str,
+    paths: &[&str],
+) -> String {
+    let body_start = text::body_start_line(source_text);
+    let same_folder =
+        source_rel.rsplit_once('/').map(|(d, _)| d) == dest_rel.rsplit_once('/').map(|(d, _)| d);
+    let edits: Vec<TextEdit> =
+        references::resolved_links_in_text(resolver, source_text, source_rel)
+            .into_iter()
+            .filter(|(target, found)| {
+                found.format != RefFormat::FrontmatterValue
+                    && (!same_folder || target == source_rel)
+            })
+            .map(|(target, found)| {
+                let target = if target == source_rel {
+                    dest_rel.to_string()
+                } else {
+                    target
+                };
+                TextEdit {
+                    range: found.range,
+                    new_text: references::retarget_ref(config, &found, dest_rel, &target, paths),
+                }
+            })
+            .collect();
+    let moved = text::apply_edits(source_text, &edits);
+    moved
+        .lines()
+        .skip(body_start)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// An edit appending `body` under a `## heading` at the end of `text`.
+fn append_edit(text: &str, heading: &str, body: &str) -> TextEdit {
+    let lines: Vec<&str> = text.lines().collect();
+    let (end, mut new_text) = if text.ends_with('\n') || text.is_empty() {
+        (Position::new(lines.len() as u32, 0), String::new())
+    } else {
+        let last = lines.last().copied().unwrap_or("");
+        (
+            Position::new((lines.len() - 1) as u32, last.encode_utf16().count() as u32),
+            String::from("\n"),
+        )
+    };
+    if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+        new_text.push('\n');
+    }
+    new_text.push_str(&format!("## {}\n", heading));
+    if !body.is_empty() {
+        new_text.push_str(&format!("\n{}\n", body));
+    }
+    TextEdit {
+        range: Range::new(end, end),
+        new_text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn merges_lists_and_keeps_destination_scalars() {
+        let source = map(serde_json::json!({
+            "type": "person",
+            "tags": ["a", "c"],
+            "status": "done",
+            "email": "x@example.com",
+            "related": "[[b]]",
+        }));
+        let dest = map(serde_json::json!({
+            "type": "contact",
+            "tags": ["a", "b"],
+            "status": "draft",
+            "related": ["[[a]]"],
+        }));
+        let (changes, conflicts) =
+            merge_frontmatter(source, dest, |k| k == "type", |k| k == "tags");
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange::Added("email".to_string(), serde_json::json!("x@example.com")),
+                FieldChange::Extended("related".to_string(), vec![serde_json::json!("[[b]]")]),
+                FieldChange::Extended("tags".to_string(), vec![serde_json::json!("c")]),
+            ]
+        );
+        let fields: Vec<&str> = conflicts.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["status", "type"]);
+    }
+
+    #[test]
+    fn frontmatter_edits_follow_list_style() {
+        let text = "---\ntags:\n  - a\nrelated: [x]\n---\nbody\n";
+        let changes = vec![
+            FieldChange::Extended("tags".to_string(), vec![serde_json::json!("b")]),
+            FieldChange::Extended("related".to_string(), vec![serde_json::json!("y")]),
+            FieldChange::Added("status".to_string(), serde_json::json!("draft")),
+        ];
+        let fields = map(serde_json::json!({ "tags": ["a"], "related": ["x"] }));
+        let (edits, replaced) = frontmatter_edits(text, &fields, &changes);
+        assert_eq!(
+            text::apply_edits(text, &edits),
+            "---\ntags:\n  - a\n  - b\nrelated: [x, y]\nstatus: draft\n---\nbody\n"
+        );
+        assert_eq!(replaced, vec![(3, 4)]);
+    }
+
+    #[test]
+    fn append_edit_adds_heading_after_a_blank_line() {
+        let edit = append_edit("# Dest\ntext", "Source", "moved");
+        assert_eq!(
+            text::apply_edits("# Dest\ntext", &[edit]),
+            "# Dest\ntext\n\n## Source\n\nmoved\n"
+        );
+    }
+}
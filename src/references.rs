@@ -1,11 +1,19 @@
 use std::collections::HashMap;
 
+use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
 use crate::body_links::{self, LinkFormat};
 use crate::collection_utils;
+use crate::config::ServerConfig;
+use crate::enum_values;
+use crate::field_refs;
+use crate::link_resolve;
+use crate::link_style;
 use crate::state::BackendState;
+use crate::tag_refs;
 use crate::text;
+use crate::type_refs;
 
 pub(crate) fn provide(state: &BackendState, params: ReferenceParams) -> Option<Vec<Location>> {
     let collection = state.get_collection()?;
@@ -13,23 +21,54 @@ pub(crate) fn provide(state: &BackendState, params: ReferenceParams) -> Option<V
     let position = params.text_document_position.position;
     let source_text = state.document_text(uri)?;
     let source_rel = collection_utils::rel_path_from_uri(&collection, uri)?;
-    let symbol = symbol_at_position(&collection, &source_text, &source_rel, position)?;
 
+    if let Some(field) =
+        field_refs::field_at_position(state, &collection, uri, &source_text, position)
+    {
+        return Some(field_refs::references(
+            state,
+            &collection,
+            &field,
+            params.context.include_declaration,
+        ));
+    }
+
+    if let Some(found) = type_refs::type_at_position(&collection, uri, &source_text, position) {
+        let mut locations = Vec::new();
+        if params.context.include_declaration {
+            if let Some(path) =
+                collection_utils::find_type_definition_path(&collection, &found.name)
+            {
+                if let Ok(type_uri) = Url::from_file_path(&path) {
+                    locations.push(Location {
+                        uri: type_uri,
+                        range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+                    });
+                }
+            }
+        }
+        locations.extend(type_refs::documents_of_type(
+            state,
+            &collection,
+            &found.name,
+        ));
+        return Some(locations);
+    }
+
+    if let Some(tag) = tag_refs::tag_at_position(&source_text, position) {
+        let mut locations = tag_refs::references(state, &collection, &tag.name);
+        if !params.context.include_declaration {
+            locations.retain(|loc| !(loc.uri == *uri && loc.range == tag.range));
+        }
+        return Some(locations);
+    }
+
+    let symbol = symbol_at_position(state, &collection, &source_text, &source_rel, position)?;
+
+    let resolver = link_resolve::IndexResolver::for_request(state, &collection);
     let mut locations = Vec::new();
-    let files = collection_utils::scan_collection_files(&collection);
-    for path in files {
-        let Ok(file_uri) = Url::from_file_path(&path) else {
-            continue;
-        };
-        let rel_path = match path.strip_prefix(&collection.root) {
-            Ok(p) => p.to_string_lossy().to_string().replace('\\', "/"),
-            Err(_) => continue,
-        };
-        let text = state
-            .document_text(&file_uri)
-            .or_else(|| std::fs::read_to_string(&path).ok())
-            .unwrap_or_default();
-        let refs = find_references_in_text(&collection, &text, &rel_path, &symbol.target);
+    for (file_uri, rel_path, text) in collection_utils::collection_texts(state, &collection) {
+        let refs = find_references_in_text(&resolver, &text, &rel_path, &symbol.target);
         locations.extend(refs.into_iter().map(|r| Location {
             uri: file_uri.clone(),
             range: r.range,
@@ -51,37 +90,118 @@ pub(crate) fn prepare_rename(
     let position = params.position;
     let source_text = state.document_text(uri)?;
     let source_rel = collection_utils::rel_path_from_uri(&collection, uri)?;
-    let symbol = symbol_at_position(&collection, &source_text, &source_rel, position)?;
+
+    if let Some(field) =
+        field_refs::field_at_position(state, &collection, uri, &source_text, position)
+    {
+        return Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: field.range,
+            placeholder: field.name,
+        });
+    }
+
+    if let Some(found) =
+        enum_values::value_at_position(state, &collection, uri, &source_text, position)
+    {
+        return Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: found.range,
+            placeholder: found.value,
+        });
+    }
+
+    if let Some(found) = type_refs::type_at_position(&collection, uri, &source_text, position) {
+        return Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: found.range,
+            placeholder: found.name,
+        });
+    }
+
+    if let Some(tag) = tag_refs::tag_at_position(&source_text, position) {
+        return Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: tag.range,
+            placeholder: tag.name,
+        });
+    }
+
+    let symbol = symbol_at_position(state, &collection, &source_text, &source_rel, position)?;
     Some(PrepareRenameResponse::RangeWithPlaceholder {
         range: symbol.range,
         placeholder: symbol.target,
     })
 }
 
-pub(crate) fn rename(state: &BackendState, params: RenameParams) -> Option<WorkspaceEdit> {
-    let collection = state.get_collection()?;
+pub(crate) fn rename(state: &BackendState, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+    let Some(collection) = state.get_collection() else {
+        return Ok(None);
+    };
     let uri = &params.text_document_position.text_document.uri;
     let position = params.text_document_position.position;
-    let source_text = state.document_text(uri)?;
-    let source_rel = collection_utils::rel_path_from_uri(&collection, uri)?;
-    let symbol = symbol_at_position(&collection, &source_text, &source_rel, position)?;
+    let Some(source_text) = state.document_text(uri) else {
+        return Ok(None);
+    };
 
-    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
-    let files = collection_utils::scan_collection_files(&collection);
-    for path in files {
-        let Ok(file_uri) = Url::from_file_path(&path) else {
-            continue;
-        };
-        let rel_path = match path.strip_prefix(&collection.root) {
-            Ok(p) => p.to_string_lossy().to_string().replace('\\', "/"),
-            Err(_) => continue,
-        };
-        let text = state
-            .document_text(&file_uri)
-            .or_else(|| std::fs::read_to_string(&path).ok())
-            .unwrap_or_default();
+    if let Some(field) =
+        field_refs::field_at_position(state, &collection, uri, &source_text, position)
+    {
+        return field_refs::rename(state, &collection, &field, &params.new_name);
+    }
+    Ok(rename_value_or_link(
+        state,
+        &collection,
+        uri,
+        &source_text,
+        position,
+        &params.new_name,
+    ))
+}
+
+/// Rename an enum value, type, tag or link target at `position`.
+fn rename_value_or_link(
+    state: &BackendState,
+    collection: &mdbase::Collection,
+    uri: &Url,
+    source_text: &str,
+    position: Position,
+    new_name: &str,
+) -> Option<WorkspaceEdit> {
+    let source_rel = collection_utils::rel_path_from_uri(collection, uri)?;
+
+    if let Some(found) =
+        enum_values::value_at_position(state, collection, uri, source_text, position)
+    {
+        return enum_values::rename(state, collection, &found, new_name);
+    }
+
+    if let Some(found) = type_refs::type_at_position(collection, uri, source_text, position) {
+        return type_refs::rename(state, collection, &found.name, new_name);
+    }
+
+    if let Some(tag) = tag_refs::tag_at_position(source_text, position) {
+        return tag_refs::rename(state, collection, &tag.name, new_name);
+    }
 
-        let refs = find_references_in_text(&collection, &text, &rel_path, &symbol.target);
+    let symbol = symbol_at_position(state, collection, source_text, &source_rel, position)?;
+
+    let config = state.config();
+    let files = collection_utils::scan_collection_files(collection);
+    let rel_paths: Vec<String> = files
+        .iter()
+        .filter_map(|p| p.strip_prefix(&collection.root).ok())
+        .map(|p| p.to_string_lossy().to_string().replace('\\', "/"))
+        .collect();
+    // The old target is on its way out, so it doesn't make the new stem
+    // ambiguous.
+    let other_paths: Vec<&str> = rel_paths
+        .iter()
+        .map(String::as_str)
+        .filter(|p| *p != symbol.target)
+        .collect();
+    let new_target = target_rel_path(new_name);
+
+    let resolver = link_resolve::IndexResolver::for_request(state, collection);
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for (file_uri, rel_path, text) in collection_utils::collection_texts(state, collection) {
+        let refs = find_references_in_text(&resolver, &text, &rel_path, &symbol.target);
         if refs.is_empty() {
             continue;
         }
@@ -89,7 +209,7 @@ pub(crate) fn rename(state: &BackendState, params: RenameParams) -> Option<Works
             .into_iter()
             .map(|r| TextEdit {
                 range: r.range,
-                new_text: replacement_for_ref(&r, &params.new_name),
+                new_text: retarget_ref(&config, &r, &rel_path, &new_target, &other_paths),
             })
             .collect::<Vec<_>>();
         changes.insert(file_uri, edits);
@@ -102,27 +222,37 @@ pub(crate) fn rename(state: &BackendState, params: RenameParams) -> Option<Works
 }
 
 #[derive(Debug, Clone)]
-struct SymbolAtCursor {
-    target: String,
-    range: Range,
+pub(crate) struct SymbolAtCursor {
+    pub target: String,
+    pub range: Range,
 }
 
 #[derive(Debug, Clone)]
-struct FoundRef {
-    range: Range,
-    format: RefFormat,
-    alias: Option<String>,
-    anchor: Option<String>,
+pub(crate) struct FoundRef {
+    pub range: Range,
+    pub format: RefFormat,
+    pub alias: Option<String>,
+    pub anchor: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum RefFormat {
+pub(crate) enum RefFormat {
     Wikilink,
     Markdown,
     FrontmatterValue,
 }
 
-fn symbol_at_position(
+impl From<LinkFormat> for RefFormat {
+    fn from(format: LinkFormat) -> Self {
+        match format {
+            LinkFormat::Wikilink => RefFormat::Wikilink,
+            LinkFormat::Markdown => RefFormat::Markdown,
+        }
+    }
+}
+
+pub(crate) fn symbol_at_position(
+    state: &BackendState,
     collection: &mdbase::Collection,
     text: &str,
     source_rel: &str,
@@ -133,7 +263,7 @@ fn symbol_at_position(
 
     if let Some(link) = body_links::body_link_at(text, line, col) {
         let resolved =
-            collection_utils::resolve_link_target(collection, &link.target, Some(source_rel))?;
+            link_resolve::resolve_target(state, collection, &link.target, Some(source_rel))?;
         let rel = resolved
             .strip_prefix(&collection.root)
             .ok()
@@ -153,7 +283,7 @@ fn symbol_at_position(
     let line_text = text.lines().nth(line)?;
     let value = text::value_from_frontmatter_line(line_text, col)?;
     let parsed = collection_utils::parse_link_value(&value)?;
-    let resolved = collection_utils::resolve_link_target(collection, &parsed, Some(source_rel))?;
+    let resolved = link_resolve::resolve_target(state, collection, &parsed, Some(source_rel))?;
     let rel = resolved
         .strip_prefix(&collection.root)
         .ok()
@@ -168,35 +298,41 @@ fn symbol_at_position(
     })
 }
 
-fn find_references_in_text(
-    collection: &mdbase::Collection,
+pub(crate) fn find_references_in_text(
+    resolver: &link_resolve::IndexResolver,
     text: &str,
     source_rel: &str,
     target_rel: &str,
 ) -> Vec<FoundRef> {
+    resolved_links_in_text(resolver, text, source_rel)
+        .into_iter()
+        .filter(|(rel, _)| rel == target_rel)
+        .map(|(_, found)| found)
+        .collect()
+}
+
+/// Resolve every body link and frontmatter link value in `text`, returning
+/// the collection-relative target path alongside each reference.
+pub(crate) fn resolved_links_in_text(
+    resolver: &link_resolve::IndexResolver,
+    text: &str,
+    source_rel: &str,
+) -> Vec<(String, FoundRef)> {
     let mut refs = Vec::new();
     for link in body_links::find_body_links(text) {
-        if let Some(resolved) =
-            collection_utils::resolve_link_target(collection, &link.target, Some(source_rel))
-        {
-            let rel = resolved
-                .strip_prefix(&collection.root)
-                .ok()
-                .map(|p| p.to_string_lossy().to_string().replace('\\', "/"));
-            if rel.as_deref() == Some(target_rel) {
-                refs.push(FoundRef {
+        if let Some(rel) = resolver.resolve(&link.target, Some(source_rel)) {
+            refs.push((
+                rel.to_string(),
+                FoundRef {
                     range: Range {
                         start: Position::new(link.start_line as u32, link.start_col as u32),
                         end: Position::new(link.end_line as u32, link.end_col as u32),
                     },
-                    format: match link.format {
-                        LinkFormat::Wikilink => RefFormat::Wikilink,
-                        LinkFormat::Markdown => RefFormat::Markdown,
-                    },
+                    format: link.format.into(),
                     alias: link.alias.clone(),
                     anchor: link.anchor.clone(),
-                });
-            }
+                },
+            ));
         }
     }
 
@@ -205,28 +341,32 @@ fn find_references_in_text(
             if line_idx < start || line_idx > end {
                 continue;
             }
-            if let Some(value) = text::value_from_frontmatter_line(line_text, line_text.len()) {
-                if let Some(parsed) = collection_utils::parse_link_value(&value) {
-                    if let Some(resolved) =
-                        collection_utils::resolve_link_target(collection, &parsed, Some(source_rel))
-                    {
-                        let rel = resolved
-                            .strip_prefix(&collection.root)
-                            .ok()
-                            .map(|p| p.to_string_lossy().to_string().replace('\\', "/"));
-                        if rel.as_deref() == Some(target_rel) {
-                            refs.push(FoundRef {
-                                range: Range {
-                                    start: Position::new(line_idx as u32, 0),
-                                    end: Position::new(line_idx as u32, line_text.len() as u32),
-                                },
-                                format: RefFormat::FrontmatterValue,
-                                alias: None,
-                                anchor: None,
-                            });
-                        }
-                    }
-                }
+            let Some((target, range)) = frontmatter_link_target(line_idx, line_text) else {
+                continue;
+            };
+            // An unquoted `key: [[x]]` is already a body link; a second,
+            // overlapping reference would make rename edits collide.
+            let overlaps = |other: &Range| {
+                other.start.line == range.start.line
+                    && other.start.character < range.end.character
+                    && range.start.character < other.end.character
+            };
+            if refs
+                .iter()
+                .any(|(_, found): &(String, FoundRef)| overlaps(&found.range))
+            {
+                continue;
+            }
+            if let Some(rel) = resolver.resolve(target, Some(source_rel)) {
+                refs.push((
+                    rel.to_string(),
+                    FoundRef {
+                        range,
+                        format: RefFormat::FrontmatterValue,
+                        alias: None,
+                        anchor: None,
+                    },
+                ));
             }
         }
     }
@@ -234,7 +374,55 @@ fn find_references_in_text(
     refs
 }
 
-fn replacement_for_ref(found: &FoundRef, new_target: &str) -> String {
+/// The link target in the value on frontmatter line `line_idx`, with its
+/// span, so retargeting keeps the key, quotes, brackets, alias and anchor
+/// around it.
+fn frontmatter_link_target(line_idx: usize, line_text: &str) -> Option<(&str, Range)> {
+    let offset = text::frontmatter_value_offset(line_text)?;
+    let (start, end) = collection_utils::link_value_target_span(&line_text[offset..])?;
+    let (start, end) = (offset + start, offset + end);
+    let col = |byte: usize| line_text[..byte].encode_utf16().count() as u32;
+    let range = Range::new(
+        Position::new(line_idx as u32, col(start)),
+        Position::new(line_idx as u32, col(end)),
+    );
+    Some((&line_text[start..end], range))
+}
+
+/// The collection-relative path named by a rename's new name, with `.md`
+/// added when it has no extension.
+fn target_rel_path(new_name: &str) -> String {
+    let name = new_name.trim().trim_start_matches('/');
+    let has_extension = name
+        .rsplit('/')
+        .next()
+        .is_some_and(|file| file.contains('.'));
+    if has_extension {
+        name.to_string()
+    } else {
+        format!("{}.md", name)
+    }
+}
+
+/// The text of `found` pointed at `target_rel`, styled by the configured
+/// link style as seen from `source_rel`. For frontmatter values `found` spans
+/// only the target inside the value, which is styled like a wikilink target.
+pub(crate) fn retarget_ref(
+    config: &ServerConfig,
+    found: &FoundRef,
+    source_rel: &str,
+    target_rel: &str,
+    paths: &[&str],
+) -> String {
+    let format = match found.format {
+        RefFormat::Markdown => LinkFormat::Markdown,
+        RefFormat::Wikilink | RefFormat::FrontmatterValue => LinkFormat::Wikilink,
+    };
+    let target = link_style::link_target(config, format, Some(source_rel), target_rel, paths);
+    replacement_for_ref(found, &target)
+}
+
+pub(crate) fn replacement_for_ref(found: &FoundRef, new_target: &str) -> String {
     match found.format {
         RefFormat::Wikilink => {
             let mut s = new_target.to_string();
@@ -292,4 +480,61 @@ mod tests {
             "[Read](notes/new.md)"
         );
     }
+
+    #[test]
+    fn rename_target_gets_md_extension() {
+        assert_eq!(target_rel_path("notes/new"), "notes/new.md");
+        assert_eq!(target_rel_path("/notes/new.md"), "notes/new.md");
+        assert_eq!(target_rel_path("v1.2/new"), "v1.2/new.md");
+    }
+
+    fn resolver(paths: &[&str]) -> link_resolve::IndexResolver {
+        let entries: Vec<crate::file_index::FileEntry> = paths
+            .iter()
+            .map(|p| crate::file_index::FileEntry {
+                rel_path: p.to_string(),
+                types: Vec::new(),
+                tags: Vec::new(),
+                display_name: None,
+                title: None,
+                id: None,
+                aliases: Vec::new(),
+                links: Vec::new(),
+                field_values: Default::default(),
+            })
+            .collect();
+        link_resolve::IndexResolver::new(&entries, &[], &[])
+    }
+
+    #[test]
+    fn frontmatter_values_retarget_only_the_link() {
+        let text = "---\nup: [[old|see old]]\nsource: old.md\nrelated:\n  - ./old\n---\nBody\n";
+        let resolver = resolver(&["a.md", "old.md"]);
+        let found = find_references_in_text(&resolver, text, "a.md", "old.md");
+        let spans: Vec<(u32, u32, u32)> = found
+            .iter()
+            .map(|r| {
+                (
+                    r.range.start.line,
+                    r.range.start.character,
+                    r.range.end.character,
+                )
+            })
+            .collect();
+        // `up` is a body link; its frontmatter reading is not reported again.
+        assert_eq!(spans, vec![(1, 4, 19), (2, 8, 14), (4, 4, 9)]);
+
+        let config = ServerConfig::default();
+        let edits: Vec<TextEdit> = found
+            .iter()
+            .map(|r| TextEdit {
+                range: r.range,
+                new_text: retarget_ref(&config, r, "a.md", "notes/new.md", &[]),
+            })
+            .collect();
+        assert_eq!(
+            text::apply_edits(text, &edits),
+            "---\nup: [[notes/new|see old]]\nsource: notes/new\nrelated:\n  - notes/new\n---\nBody\n"
+        );
+    }
 }
@@ -2,10 +2,12 @@ use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::request::{GotoImplementationParams, GotoImplementationResponse};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 use tracing::{info, warn};
 
+use crate::config::ServerConfig;
 use crate::state::{BackendState, DocumentState};
 
 pub struct MdbaseLanguageServer {
@@ -44,6 +46,21 @@ impl LanguageServer for MdbaseLanguageServer {
             warn!("no workspace folder or root_uri provided");
         }
 
+        let type_hierarchy_registration = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|td| td.type_hierarchy.as_ref())
+            .and_then(|th| th.dynamic_registration)
+            .unwrap_or(false);
+        self.state
+            .type_hierarchy_registration
+            .store(type_hierarchy_registration, Ordering::Relaxed);
+
+        if let Some(options) = &params.initialization_options {
+            *self.state.config.write().unwrap() = ServerConfig::from_value(options);
+        }
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -63,15 +80,26 @@ impl LanguageServer for MdbaseLanguageServer {
                         "[".into(), // wikilink start
                         "(".into(), // markdown link ](
                         "#".into(), // tag
+                        "/".into(), // nested tag segment
                     ]),
-                    resolve_provider: Some(false),
+                    resolve_provider: Some(true),
                     ..Default::default()
                 }),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
+                implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
                 rename_provider: Some(OneOf::Left(true)),
-                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR_EXTRACT,
+                        ]),
+                        ..Default::default()
+                    },
+                )),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
                 document_link_provider: Some(DocumentLinkOptions {
                     resolve_provider: Some(false),
@@ -83,6 +111,8 @@ impl LanguageServer for MdbaseLanguageServer {
                         "mdbase.typeInfo".to_string(),
                         "mdbase.validateCollection".to_string(),
                         "mdbase.queryCollection".to_string(),
+                        "mdbase.splitNote".to_string(),
+                        "mdbase.mergeNotes".to_string(),
                     ],
                     ..Default::default()
                 }),
@@ -97,11 +127,31 @@ impl LanguageServer for MdbaseLanguageServer {
             .log_message(MessageType::INFO, "mdbase LSP initialized")
             .await;
 
+        // lsp-types has no static capability for type hierarchy yet, so
+        // register it dynamically against the client's document selector —
+        // only for clients that support that.
+        if self
+            .state
+            .type_hierarchy_registration
+            .load(Ordering::Relaxed)
+        {
+            let registration = Registration {
+                id: "mdbase-type-hierarchy".to_string(),
+                method: "textDocument/prepareTypeHierarchy".to_string(),
+                register_options: Some(serde_json::json!({ "documentSelector": null })),
+            };
+            if let Err(e) = self.client.register_capability(vec![registration]).await {
+                warn!(error = %e, "failed to register type hierarchy provider");
+            }
+        }
+
         // Build the file index in the background
         let state = Arc::clone(&self.state);
         tokio::task::spawn_blocking(move || {
             if let Some(collection) = state.get_collection() {
-                state.file_index.rebuild(&collection);
+                state
+                    .file_index
+                    .rebuild(&collection, &state.config().aliases_field);
             }
         });
     }
@@ -119,9 +169,13 @@ impl LanguageServer for MdbaseLanguageServer {
         );
         if let Some(collection) = self.state.get_collection() {
             if let Some(rel_path) = crate::collection_utils::rel_path_from_uri(&collection, &uri) {
-                self.state
-                    .file_index
-                    .upsert_from_text(&collection, rel_path, &text);
+                self.state.touch_recent(&rel_path);
+                self.state.file_index.upsert_from_text(
+                    &collection,
+                    rel_path,
+                    &text,
+                    &self.state.config().aliases_field,
+                );
             }
         }
         // Immediate diagnostics on open
@@ -144,6 +198,11 @@ impl LanguageServer for MdbaseLanguageServer {
             }
             doc.invalidate_frontmatter();
         }
+        if let Some(collection) = self.state.get_collection() {
+            if let Some(rel_path) = crate::collection_utils::rel_path_from_uri(&collection, &uri) {
+                self.state.touch_recent(&rel_path);
+            }
+        }
 
         // Debounced diagnostics: bump generation, spawn delayed task
         let gen = self.state.bump_generation(&uri);
@@ -159,11 +218,43 @@ impl LanguageServer for MdbaseLanguageServer {
         });
     }
 
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        let config = ServerConfig::from_value(&params.settings);
+        let aliases_changed = config.aliases_field != self.state.config().aliases_field;
+        let tie_break_changed = config.link_tie_break != self.state.config().link_tie_break;
+        *self.state.config.write().unwrap() = config;
+        if tie_break_changed {
+            self.state.file_index.invalidate_links();
+        }
+
+        // Aliases are indexed, so a different field needs a fresh index.
+        if aliases_changed {
+            let state = Arc::clone(&self.state);
+            tokio::task::spawn_blocking(move || {
+                if let Some(collection) = state.get_collection() {
+                    state
+                        .file_index
+                        .rebuild(&collection, &state.config().aliases_field);
+                }
+            });
+        }
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = &params.text_document.uri;
         if let Some(collection) = self.state.get_collection() {
             if let Some(rel_path) = crate::collection_utils::rel_path_from_uri(&collection, uri) {
+                // Unsaved edits are discarded on close: index the note as it
+                // is on disk, or drop it if it no longer exists there.
                 self.state.file_index.remove_path(&rel_path);
+                if let Ok(text) = std::fs::read_to_string(collection.root.join(&rel_path)) {
+                    self.state.file_index.upsert_from_text(
+                        &collection,
+                        rel_path,
+                        &text,
+                        &self.state.config().aliases_field,
+                    );
+                }
             }
         }
         self.state.documents.remove(uri);
@@ -263,9 +354,12 @@ impl LanguageServer for MdbaseLanguageServer {
         if let Some(collection) = self.state.get_collection() {
             if let Some(rel_path) = crate::collection_utils::rel_path_from_uri(&collection, &uri) {
                 if let Some(text) = self.state.document_text(&uri) {
-                    self.state
-                        .file_index
-                        .upsert_from_text(&collection, rel_path, &text);
+                    self.state.file_index.upsert_from_text(
+                        &collection,
+                        rel_path,
+                        &text,
+                        &self.state.config().aliases_field,
+                    );
                 }
             }
         }
@@ -277,6 +371,10 @@ impl LanguageServer for MdbaseLanguageServer {
         Ok(crate::completions::provide(&self.state, uri, pos))
     }
 
+    async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
+        Ok(crate::completions::resolve(&self.state, item))
+    }
+
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = &params.text_document_position_params.text_document.uri;
         let pos = params.text_document_position_params.position;
@@ -292,6 +390,15 @@ impl LanguageServer for MdbaseLanguageServer {
         Ok(crate::goto::definition(&self.state, uri, pos))
     }
 
+    async fn goto_implementation(
+        &self,
+        params: GotoImplementationParams,
+    ) -> Result<Option<GotoImplementationResponse>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+        Ok(crate::type_refs::implementation(&self.state, uri, pos))
+    }
+
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
         Ok(crate::references::provide(&self.state, params))
     }
@@ -308,7 +415,49 @@ impl LanguageServer for MdbaseLanguageServer {
     }
 
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
-        Ok(crate::references::rename(&self.state, params))
+        crate::references::rename(&self.state, params)
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        Ok(crate::call_hierarchy::prepare(&self.state, params))
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        Ok(crate::call_hierarchy::incoming(&self.state, params))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        Ok(crate::call_hierarchy::outgoing(&self.state, params))
+    }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        Ok(crate::type_hierarchy::prepare(&self.state, params))
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        Ok(crate::type_hierarchy::supertypes(&self.state, params))
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        Ok(crate::type_hierarchy::subtypes(&self.state, params))
     }
 
     async fn symbol(
@@ -0,0 +1,231 @@
+/// Frontmatter skeleton completions — in an empty note, or on its opening
+/// `---`, offer one snippet per type that inserts a complete frontmatter block.
+///
+/// The fields mirror what `mdbase.createFile` writes: generated values and
+/// defaults are pre-filled, and the remaining required fields become tab stops.
+use tower_lsp::lsp_types::*;
+
+use mdbase::types::schema::FieldDef;
+use mdbase::Collection;
+
+use crate::collection_utils;
+use crate::commands;
+use crate::text;
+
+/// Skeleton items for the cursor position, or `None` when it is not at the
+/// start of a note without frontmatter.
+pub(crate) fn completions(
+    collection: &Collection,
+    uri: &Url,
+    text: &str,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    if position.line != 0 || text::frontmatter_bounds(text).is_some() {
+        return None;
+    }
+    let first_line = text.lines().next().unwrap_or("");
+    let typed = first_line.trim_end();
+    if !matches!(typed, "" | "-" | "--" | "---") {
+        return None;
+    }
+    if typed.is_empty() && !text.trim().is_empty() {
+        return None;
+    }
+
+    // An empty `---` pair is replaced as a whole.
+    let mut end = Position::new(0, first_line.len() as u32);
+    if typed == "---" {
+        if let Some(second) = text.lines().nth(1).filter(|l| l.trim_end() == "---") {
+            end = Position::new(1, second.len() as u32);
+        }
+    }
+    let range = Range::new(Position::new(0, 0), end);
+
+    let rel_path = collection_utils::rel_path_from_uri(collection, uri);
+    let matched = collection.determine_types_for_path(&serde_json::json!({}), rel_path.as_deref());
+    let mut type_names: Vec<String> = collection.types.keys().cloned().collect();
+    type_names.sort_by_key(|name| {
+        let rank = matched
+            .iter()
+            .position(|m| m.eq_ignore_ascii_case(name))
+            .unwrap_or(usize::MAX);
+        (rank, name.clone())
+    });
+
+    let items = type_names
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, name)| {
+            let is_match = matched.iter().any(|m| m.eq_ignore_ascii_case(name));
+            skeleton_item(collection, name, is_match, idx, range)
+        })
+        .collect();
+    Some(items)
+}
+
+fn skeleton_item(
+    collection: &Collection,
+    type_name: &str,
+    is_match: bool,
+    rank: usize,
+    range: Range,
+) -> Option<CompletionItem> {
+    let type_def = collection.types.get(type_name)?;
+    let (snippet, preview) = build_skeleton(collection, type_name, serde_json::Map::new());
+
+    let mut detail = format!("New {} note", type_def.name);
+    if is_match {
+        detail.push_str(" (matches this path)");
+    }
+    Some(CompletionItem {
+        label: format!("{} frontmatter", type_def.name),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some(detail),
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("```yaml\n{}```", preview),
+        })),
+        filter_text: Some(format!("---{}", type_def.name)),
+        sort_text: Some(format!("{:04}", rank)),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range,
+            new_text: snippet,
+        })),
+        ..Default::default()
+    })
+}
+
+/// A plain frontmatter block for a new note of `type_name`, with `initial`
+/// values, generated values and defaults filled in and the remaining required
+/// fields left empty.
+pub(crate) fn frontmatter_block(
+    collection: &Collection,
+    type_name: &str,
+    initial: serde_json::Map<String, serde_json::Value>,
+) -> String {
+    build_skeleton(collection, type_name, initial).1
+}
+
+/// Build the snippet text and a plain preview of the frontmatter block.
+fn build_skeleton(
+    collection: &Collection,
+    type_name: &str,
+    initial: serde_json::Map<String, serde_json::Value>,
+) -> (String, String) {
+    let Some(type_def) = collection.types.get(type_name) else {
+        return (String::new(), String::new());
+    };
+    let mut prefilled = initial;
+    commands::prefill_frontmatter(collection, type_name, type_def, &mut prefilled);
+
+    let mut snippet = String::from("---\n");
+    let mut preview = String::from("---\n");
+    // The type goes under the first key type resolution reads; match-rule
+    // collections have no such key and leave it out.
+    let type_key = collection.settings.explicit_type_keys.first();
+    if let Some(type_key) = type_key {
+        let line = format!("{}: {}\n", type_key, type_def.name);
+        snippet.push_str(&escape_snippet(&line));
+        preview.push_str(&line);
+    }
+
+    let mut field_names: Vec<&String> = type_def.fields.keys().collect();
+    field_names.sort();
+    let mut tab_stop = 1;
+    for field_name in field_names {
+        if type_key == Some(field_name) {
+            continue;
+        }
+        let field_def = &type_def.fields[field_name];
+        if let Some(value) = prefilled.get(field_name) {
+            let line = format!("{}: {}\n", field_name, yaml_scalar(value));
+            snippet.push_str(&escape_snippet(&line));
+            preview.push_str(&line);
+        } else if commands::is_prompt_field(collection, type_name, field_name, field_def) {
+            snippet.push_str(&format!(
+                "{}: {}\n",
+                escape_snippet(field_name),
+                placeholder(field_def, tab_stop)
+            ));
+            preview.push_str(&format!("{}: \n", field_name));
+            tab_stop += 1;
+        }
+    }
+    snippet.push_str("---\n$0");
+    preview.push_str("---\n");
+    (snippet, preview)
+}
+
+/// A tab stop for a required field — a choice for enums and booleans.
+fn placeholder(def: &FieldDef, tab_stop: usize) -> String {
+    let choices: Option<Vec<String>> = match (&def.values, def.field_type.as_str()) {
+        (Some(values), _) if !values.is_empty() => Some(values.clone()),
+        (_, "boolean") => Some(vec!["true".to_string(), "false".to_string()]),
+        _ => None,
+    };
+    match choices {
+        Some(choices) => {
+            let escaped: Vec<String> = choices
+                .iter()
+                .map(|c| {
+                    c.replace('\\', "\\\\")
+                        .replace(',', "\\,")
+                        .replace('|', "\\|")
+                })
+                .collect();
+            format!("${{{}|{}|}}", tab_stop, escaped.join(","))
+        }
+        None => format!("${{{}}}", tab_stop),
+    }
+}
+
+/// Render a JSON value as an inline YAML value.
+pub(crate) fn yaml_scalar(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => {
+            let needs_quotes = s.is_empty()
+                || s.contains(": ")
+                || s.contains(" #")
+                || s.starts_with(|c: char| "[]{}&*!|>'\"%@`#,-?:".contains(c))
+                || matches!(s.as_str(), "true" | "false" | "null" | "~")
+                || s.parse::<f64>().is_ok();
+            if needs_quotes {
+                serde_json::to_string(s).unwrap_or_default()
+            } else {
+                s.clone()
+            }
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(yaml_scalar).collect();
+            format!("[{}]", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Escape text so it is inserted literally by a snippet.
+fn escape_snippet(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_scalar_quotes_ambiguous_strings() {
+        assert_eq!(yaml_scalar(&serde_json::json!("draft")), "draft");
+        assert_eq!(yaml_scalar(&serde_json::json!("42")), "\"42\"");
+        assert_eq!(yaml_scalar(&serde_json::json!("a: b")), "\"a: b\"");
+        assert_eq!(yaml_scalar(&serde_json::json!(["a", "b"])), "[a, b]");
+        assert_eq!(yaml_scalar(&serde_json::json!(3)), "3");
+    }
+
+    #[test]
+    fn escape_snippet_escapes_special_characters() {
+        assert_eq!(escape_snippet("cost: $5 {x}"), "cost: \\$5 {x\\}");
+    }
+}
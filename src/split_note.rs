@@ -0,0 +1,372 @@
+/// Split note — breaks a note into one note per heading of a chosen level.
+///
+/// Each section moves to a new note next to the parent, carrying the selected
+/// frontmatter fields, and is replaced in the parent by a link or embed. Links
+/// that pointed at `parent#Heading` (or a heading inside a section) follow the
+/// section to its new note.
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::body_links::LinkFormat;
+use crate::collection_utils;
+use crate::extract_note;
+use crate::link_resolve;
+use crate::link_style;
+use crate::references::{self, FoundRef};
+use crate::skeleton;
+use crate::state::BackendState;
+use crate::text;
+
+/// How `mdbase.splitNote` splits a note.
+#[derive(Debug, Clone)]
+pub(crate) struct SplitOptions {
+    /// Heading level (1–6) that starts a new note.
+    pub level: usize,
+    /// Top-level frontmatter fields copied into every new note.
+    pub inherit: Vec<String>,
+    /// Replace sections with `![[embeds]]` instead of a list of links.
+    pub embed: bool,
+}
+
+/// One heading-delimited section of the parent note.
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+    title: String,
+    /// First line (the heading) and the line after the last, in the parent.
+    start_line: usize,
+    end_line: usize,
+    /// Titles of the deeper headings inside the section.
+    subheadings: Vec<String>,
+}
+
+/// The edit splitting the note at `uri`, and the paths of the notes it
+/// creates, or `None` when the note has no heading of the chosen level.
+pub(crate) fn split_edit(
+    state: &BackendState,
+    collection: &Collection,
+    uri: &Url,
+    options: &SplitOptions,
+) -> Option<(WorkspaceEdit, Vec<String>)> {
+    let parent_rel = collection_utils::rel_path_from_uri(collection, uri)?;
+    let text = state
+        .document_text(uri)
+        .or_else(|| std::fs::read_to_string(collection.root.join(&parent_rel)).ok())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let body_start = text::body_start_line(&text);
+    let sections = find_sections(&lines, body_start, options.level);
+    if sections.is_empty() {
+        return None;
+    }
+
+    // New notes sit next to the parent, named after their headings.
+    let existing = state.file_index.with_entries(|entries| {
+        entries
+            .iter()
+            .map(|e| e.rel_path.clone())
+            .collect::<Vec<_>>()
+    });
+    let dir = parent_rel.rsplit_once('/').map(|(d, _)| d);
+    let parent_stem = parent_rel
+        .rsplit('/')
+        .next()
+        .unwrap_or(&parent_rel)
+        .trim_end_matches(".md");
+    let mut new_paths: Vec<String> = Vec::new();
+    for (idx, section) in sections.iter().enumerate() {
+        let name = extract_note::note_file_name(&section.title)
+            .unwrap_or_else(|| format!("{} {}", parent_stem, idx + 1));
+        let path = match dir {
+            Some(dir) => format!("{}/{}.md", dir, name),
+            None => format!("{}.md", name),
+        };
+        let path = extract_note::unique_path(&path, |p| {
+            existing.iter().any(|e| e == p)
+                || new_paths.iter().any(|n| n == p)
+                || collection.root.join(p).exists()
+        });
+        new_paths.push(path);
+    }
+    let mut paths: Vec<&str> = existing.iter().map(String::as_str).collect();
+    paths.extend(new_paths.iter().map(String::as_str));
+
+    // Links to the parent's section headings, from every note. Those inside
+    // a section move with it.
+    let config = state.config();
+    let mut parent_edits: Vec<TextEdit> = Vec::new();
+    let mut moved_edits: Vec<TextEdit> = Vec::new();
+    let mut other_changes: Vec<DocumentChangeOperation> = Vec::new();
+    let resolver = link_resolve::IndexResolver::for_request(state, collection);
+    for (file_uri, source_rel, file_text) in collection_utils::collection_texts(state, collection) {
+        let is_parent = source_rel == parent_rel;
+        let file_text = if is_parent { text.clone() } else { file_text };
+        let mut edits = Vec::new();
+        for found in
+            references::find_references_in_text(&resolver, &file_text, &source_rel, &parent_rel)
+        {
+            let Some((idx, retargeted)) = retarget_anchor(&sections, &found) else {
+                continue;
+            };
+            let edit = TextEdit {
+                range: found.range,
+                new_text: references::retarget_ref(
+                    &config,
+                    &retargeted,
+                    &source_rel,
+                    &new_paths[idx],
+                    &paths,
+                ),
+            };
+            let line = found.range.start.line as usize;
+            if is_parent
+                && sections
+                    .iter()
+                    .any(|s| (s.start_line..s.end_line).contains(&line))
+            {
+                moved_edits.push(edit);
+            } else {
+                edits.push(edit);
+            }
+        }
+        if is_parent {
+            parent_edits = edits;
+        } else if !edits.is_empty() {
+            other_changes.push(collection_utils::text_document_edit(file_uri, edits));
+        }
+    }
+
+    // The parent keeps everything outside the sections; each section becomes
+    // a link or embed line.
+    // Link edits are within a line, so the moved text keeps its line numbers.
+    let moved_text = text::apply_edits(&text, &moved_edits);
+    let moved_lines: Vec<&str> = moved_text.lines().collect();
+    let inherited = inherited_frontmatter(&text, &options.inherit);
+    let has_title = text::top_level_key_line(&text, "title").is_some();
+    let mut operations = Vec::new();
+    for (idx, section) in sections.iter().enumerate() {
+        let new_uri = collection_utils::uri_from_rel_path(collection, &new_paths[idx])?;
+        let body = section_text(&moved_lines, section);
+        let mut content = String::new();
+        if !inherited.is_empty() || has_title {
+            content.push_str("---\n");
+            if has_title {
+                content.push_str(&format!(
+                    "title: {}\n",
+                    skeleton::yaml_scalar(&serde_json::json!(section.title))
+                ));
+            }
+            content.push_str(&inherited);
+            content.push_str("---\n");
+        }
+        content.push_str(&body);
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Create(
+            CreateFile {
+                uri: new_uri.clone(),
+                options: Some(CreateFileOptions {
+                    overwrite: Some(false),
+                    ignore_if_exists: Some(false),
+                }),
+                annotation_id: None,
+            },
+        )));
+        operations.push(collection_utils::text_document_edit(
+            new_uri,
+            vec![TextEdit {
+                range: Range::default(),
+                new_text: content,
+            }],
+        ));
+
+        let target = link_style::link_target(
+            &config,
+            LinkFormat::Wikilink,
+            Some(&parent_rel),
+            &new_paths[idx],
+            &paths,
+        );
+        let mut new_text = if options.embed {
+            format!("![[{}]]\n", target)
+        } else {
+            format!("- [[{}]]\n", target)
+        };
+        // Keep a blank line before whatever follows a run of sections.
+        let ends_blank = lines[section.start_line..section.end_line]
+            .last()
+            .is_some_and(|l| l.trim().is_empty());
+        let next_is_section = sections.get(idx + 1).map(|s| s.start_line) == Some(section.end_line);
+        if ends_blank && !next_is_section {
+            new_text.push('\n');
+        }
+        let end = if section.end_line < lines.len() || text.ends_with('\n') {
+            Position::new(section.end_line as u32, 0)
+        } else {
+            new_text.pop();
+            let last = lines[section.end_line - 1];
+            Position::new(
+                (section.end_line - 1) as u32,
+                last.encode_utf16().count() as u32,
+            )
+        };
+        parent_edits.push(TextEdit {
+            range: Range::new(Position::new(section.start_line as u32, 0), end),
+            new_text,
+        });
+    }
+    operations.push(collection_utils::text_document_edit(
+        uri.clone(),
+        parent_edits,
+    ));
+    operations.extend(other_changes);
+
+    let edit = WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    };
+    Some((edit, new_paths))
+}
+
+/// Sections starting at each heading of `level`, ending before the next
+/// heading of that level or higher. Headings in fenced code are ignored.
+fn find_sections(lines: &[&str], body_start: usize, level: usize) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut current: Option<Section> = None;
+    let mut in_fence = false;
+    for (idx, line) in lines.iter().enumerate().skip(body_start) {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some((heading_level, title)) = text::atx_heading(line) else {
+            continue;
+        };
+        if heading_level <= level {
+            if let Some(mut section) = current.take() {
+                section.end_line = idx;
+                sections.push(section);
+            }
+        }
+        if heading_level == level {
+            current = Some(Section {
+                title: title.to_string(),
+                start_line: idx,
+                end_line: lines.len(),
+                subheadings: Vec::new(),
+            });
+        } else if let Some(section) = current.as_mut() {
+            section.subheadings.push(title.to_string());
+        }
+    }
+    sections.extend(current);
+    sections
+}
+
+/// The section a `parent#anchor` reference points into, and the reference as
+/// it should read there: an anchor naming the section heading itself is
+/// dropped, one naming a deeper heading is kept.
+fn retarget_anchor(sections: &[Section], found: &FoundRef) -> Option<(usize, FoundRef)> {
+    let anchor = found.anchor.as_deref()?;
+    sections.iter().enumerate().find_map(|(idx, section)| {
+        let mut retargeted = found.clone();
+        if anchor_matches(anchor, &section.title) {
+            retargeted.anchor = None;
+        } else if !section
+            .subheadings
+            .iter()
+            .any(|h| anchor_matches(anchor, h))
+        {
+            return None;
+        }
+        Some((idx, retargeted))
+    })
+}
+
+/// Whether a link anchor names `heading`, either verbatim or as its slug.
+fn anchor_matches(anchor: &str, heading: &str) -> bool {
+    let slug = |s: &str| -> String {
+        s.trim()
+            .to_lowercase()
+            .chars()
+            .filter_map(|c| match c {
+                c if c.is_alphanumeric() || c == '_' => Some(c),
+                ' ' | '-' => Some('-'),
+                _ => None,
+            })
+            .collect()
+    };
+    anchor.trim().eq_ignore_ascii_case(heading.trim()) || slug(anchor) == slug(heading)
+}
+
+/// The section's lines with trailing blank lines dropped.
+fn section_text(lines: &[&str], section: &Section) -> String {
+    let mut out: Vec<&str> = lines[section.start_line..section.end_line.min(lines.len())].to_vec();
+    while out.last().is_some_and(|l| l.trim().is_empty()) {
+        out.pop();
+    }
+    let mut body = out.join("\n");
+    body.push('\n');
+    body
+}
+
+/// The raw frontmatter lines of each `fields` key present in `text`,
+/// including indented or list continuation lines.
+fn inherited_frontmatter(text: &str, fields: &[String]) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::new();
+    for field in fields {
+        if field == "title" {
+            continue;
+        }
+        let Some((start, end)) = text::top_level_field_lines(text, field) else {
+            continue;
+        };
+        for line in &lines[start..end] {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_end_at_same_or_higher_headings() {
+        let text = "---\ntitle: Doc\n---\n# Doc\nintro\n## A\na\n### A1\n```\n## not\n```\n## B\nb\n# Appendix\nz\n";
+        let lines: Vec<&str> = text.lines().collect();
+        let sections = find_sections(&lines, 3, 2);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].title, "A");
+        assert_eq!((sections[0].start_line, sections[0].end_line), (5, 11));
+        assert_eq!(sections[0].subheadings, vec!["A1".to_string()]);
+        assert_eq!(sections[1].title, "B");
+        assert_eq!((sections[1].start_line, sections[1].end_line), (11, 13));
+    }
+
+    #[test]
+    fn anchors_match_heading_text_or_slug() {
+        assert!(anchor_matches("Design Notes", "Design notes"));
+        assert!(anchor_matches("design-notes", "Design notes"));
+        assert!(anchor_matches("whats-next", "What's next?"));
+        assert!(!anchor_matches("design", "Design notes"));
+    }
+
+    #[test]
+    fn inherits_raw_field_blocks() {
+        let text = "---\ntitle: Doc\ntags:\n  - a\n  - b\nstatus: draft\nauthor: me\n---\nbody\n";
+        let fields = vec![
+            "tags".to_string(),
+            "author".to_string(),
+            "title".to_string(),
+        ];
+        assert_eq!(
+            inherited_frontmatter(text, &fields),
+            "tags:\n  - a\n  - b\nauthor: me\n"
+        );
+    }
+}
@@ -3,11 +3,12 @@ use ropey::Rope;
 use tower_lsp::lsp_types::Url;
 use tracing::{info, warn};
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 use mdbase::Collection;
 
+use crate::config::ServerConfig;
 use crate::file_index::FileIndex;
 use crate::text::ParsedFrontmatter;
 
@@ -62,8 +63,22 @@ pub struct BackendState {
 
     /// Cached file index for completions.
     pub file_index: FileIndex,
+
+    /// Client-provided server settings.
+    pub config: std::sync::RwLock<ServerConfig>,
+
+    /// Collection-relative paths of recently opened or edited notes, most
+    /// recent first.
+    pub recent_notes: Mutex<Vec<String>>,
+
+    /// Whether the client can register type hierarchy dynamically, from the
+    /// `initialize` capabilities.
+    pub type_hierarchy_registration: AtomicBool,
 }
 
+/// How many recently used notes are remembered for completion ranking.
+pub const MAX_RECENT_NOTES: usize = 50;
+
 impl BackendState {
     pub fn new() -> Self {
         Self {
@@ -72,6 +87,9 @@ impl BackendState {
             documents: DashMap::new(),
             diagnostics_generation: DashMap::new(),
             file_index: FileIndex::new(),
+            config: std::sync::RwLock::new(ServerConfig::default()),
+            recent_notes: Mutex::new(Vec::new()),
+            type_hierarchy_registration: AtomicBool::new(false),
         }
     }
 
@@ -109,10 +127,29 @@ impl BackendState {
         *self.collection.write().unwrap() = None;
     }
 
+    pub fn config(&self) -> ServerConfig {
+        self.config.read().unwrap().clone()
+    }
+
     pub fn document_text(&self, uri: &Url) -> Option<String> {
         self.documents.get(uri).map(|r| r.rope.to_string())
     }
 
+    /// Move a note to the front of the recently used list.
+    pub fn touch_recent(&self, rel_path: &str) {
+        let mut recent = self.recent_notes.lock().unwrap();
+        if recent.first().is_some_and(|r| r == rel_path) {
+            return;
+        }
+        recent.retain(|r| r != rel_path);
+        recent.insert(0, rel_path.to_string());
+        recent.truncate(MAX_RECENT_NOTES);
+    }
+
+    pub fn recent_notes(&self) -> Vec<String> {
+        self.recent_notes.lock().unwrap().clone()
+    }
+
     /// Get the diagnostics generation counter for a URI, creating it if needed.
     pub fn generation_counter(&self, uri: &Url) -> Arc<AtomicU64> {
         self.diagnostics_generation
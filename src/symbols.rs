@@ -76,7 +76,14 @@ fn matches_query(entry: &crate::file_index::FileEntry, query: &str) -> bool {
         let value = v.trim();
         return match k.trim() {
             "type" => entry.types.iter().any(|t| t.eq_ignore_ascii_case(value)),
-            "tag" => entry.tags.iter().any(|t| t.eq_ignore_ascii_case(value)),
+            // `tag:a/*` also matches nested tags such as `a/b`.
+            "tag" => match value.strip_suffix("/*") {
+                Some(parent) => entry
+                    .tags
+                    .iter()
+                    .any(|t| crate::tag_refs::is_tag_or_descendant(&t.to_lowercase(), parent)),
+                None => entry.tags.iter().any(|t| t.eq_ignore_ascii_case(value)),
+            },
             "id" => entry
                 .id
                 .as_deref()
@@ -115,7 +122,9 @@ mod tests {
             display_name: Some("Demo Note".to_string()),
             title: Some("Demo Note".to_string()),
             id: Some("abc-1".to_string()),
-            preview: None,
+            aliases: Vec::new(),
+            links: Vec::new(),
+            field_values: Default::default(),
         }
     }
 
@@ -128,6 +137,16 @@ mod tests {
         assert!(!matches_query(&e, "type:person"));
     }
 
+    #[test]
+    fn matches_nested_tag_queries() {
+        let mut e = entry();
+        e.tags = vec!["lang/rust".to_string()];
+        assert!(matches_query(&e, "tag:lang/*"));
+        assert!(matches_query(&e, "tag:lang/rust"));
+        assert!(!matches_query(&e, "tag:lang"));
+        assert!(!matches_query(&e, "tag:rust"));
+    }
+
     #[test]
     fn matches_free_text_queries() {
         let e = entry();
@@ -0,0 +1,389 @@
+/// Tag navigation — finds `#tags` in note bodies and values of the frontmatter
+/// `tags` field, and builds references and rename edits across the collection.
+///
+/// Frontmatter ranges use byte columns like the rest of the frontmatter code;
+/// body ranges use UTF-16 columns like `body_links`.
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::collection_utils;
+use crate::state::BackendState;
+use crate::text;
+
+/// A tag occurrence in a document.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TagOccurrence {
+    /// Tag name without the leading `#`.
+    pub name: String,
+    /// Range of the name (excluding any `#`).
+    pub range: Range,
+    /// Whether the tag is a frontmatter `tags` value rather than a body `#tag`.
+    pub in_frontmatter: bool,
+}
+
+/// Find the tag under the cursor, in the frontmatter `tags` field or the body.
+pub(crate) fn tag_at_position(text: &str, position: Position) -> Option<TagOccurrence> {
+    let line = position.line;
+    let column = position.character;
+    find_tags(text).into_iter().find(|tag| {
+        tag.range.start.line == line
+            && column >= tag.range.start.character
+            && column <= tag.range.end.character
+    })
+}
+
+/// Every tag occurrence in a document: frontmatter `tags` values followed by
+/// body tags. Fenced code blocks and inline code spans are skipped.
+pub(crate) fn find_tags(text: &str) -> Vec<TagOccurrence> {
+    let mut tags = Vec::new();
+    let bounds = text::frontmatter_bounds(text);
+    let body_start = text::body_start_line(text);
+
+    if let Some((start, end)) = bounds {
+        for (line_idx, line) in text.lines().enumerate() {
+            if line_idx < start || line_idx > end {
+                continue;
+            }
+            let nested_key =
+                line.starts_with(char::is_whitespace) && !line.trim_start().starts_with('-');
+            if nested_key
+                || text::field_name_for_position(text, line_idx).as_deref() != Some("tags")
+            {
+                continue;
+            }
+            for (s, e) in text::value_tokens(line) {
+                let s = if line[s..e].starts_with('#') {
+                    s + 1
+                } else {
+                    s
+                };
+                if s >= e {
+                    continue;
+                }
+                tags.push(TagOccurrence {
+                    name: line[s..e].to_string(),
+                    range: Range::new(
+                        Position::new(line_idx as u32, s as u32),
+                        Position::new(line_idx as u32, e as u32),
+                    ),
+                    in_frontmatter: true,
+                });
+            }
+        }
+    }
+
+    let mut in_fenced_block = false;
+    for (line_idx, line) in text.lines().enumerate().skip(body_start) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fenced_block = !in_fenced_block;
+            continue;
+        }
+        if in_fenced_block {
+            continue;
+        }
+        for (name, start_col, end_col) in body_tags_in_line(line) {
+            tags.push(TagOccurrence {
+                name,
+                range: Range::new(
+                    Position::new(line_idx as u32, start_col as u32),
+                    Position::new(line_idx as u32, end_col as u32),
+                ),
+                in_frontmatter: false,
+            });
+        }
+    }
+    tags
+}
+
+/// Body `#tags` on a line as (name, start, end) with UTF-16 columns of the
+/// name. A tag starts at a `#` preceded by whitespace or the line start and
+/// must contain at least one non-digit, so headings and `#123` are skipped.
+fn body_tags_in_line(line: &str) -> Vec<(String, usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut result = Vec::new();
+    let mut col = 0usize;
+    let mut i = 0usize;
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch == '`' {
+            // Skip inline code spans.
+            col += 1;
+            i += 1;
+            while i < chars.len() && chars[i] != '`' {
+                col += chars[i].len_utf16();
+                i += 1;
+            }
+            if i < chars.len() {
+                col += 1;
+                i += 1;
+            }
+            continue;
+        }
+        let at_boundary = i == 0 || chars[i - 1].is_whitespace();
+        if ch == '#' && at_boundary {
+            let start = i + 1;
+            let mut end = start;
+            while end < chars.len() && is_tag_char(chars[end]) {
+                end += 1;
+            }
+            let name: String = chars[start..end].iter().collect();
+            let name = name.trim_end_matches('/').to_string();
+            if !name.is_empty() && !name.chars().all(|c| c.is_ascii_digit()) {
+                let start_col = col + 1;
+                let end_col = start_col + name.encode_utf16().count();
+                result.push((name, start_col, end_col));
+            }
+            for c in &chars[i..end] {
+                col += c.len_utf16();
+            }
+            i = end;
+            continue;
+        }
+        col += ch.len_utf16();
+        i += 1;
+    }
+    result
+}
+
+fn is_tag_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '-' | '/')
+}
+
+/// Whether `tag` is `parent` itself or nested below it (`parent/child`).
+pub(crate) fn is_tag_or_descendant(tag: &str, parent: &str) -> bool {
+    tag == parent
+        || tag
+            .strip_prefix(parent)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// `tag` and each of its parents: `a/b/c` → `a`, `a/b`, `a/b/c`.
+pub(crate) fn tag_with_ancestors(tag: &str) -> impl Iterator<Item = &str> {
+    tag.match_indices('/')
+        .map(move |(idx, _)| &tag[..idx])
+        .chain(std::iter::once(tag))
+}
+
+/// The partial tag being typed before `column` (without `#`), if the cursor
+/// is inside a body `#tag`.
+pub(crate) fn tag_prefix_at(line: &str, column: usize) -> Option<String> {
+    let before: String = line.chars().take(column).collect();
+    let hash = before.rfind('#')?;
+    let partial = &before[hash + 1..];
+    let at_boundary = before[..hash]
+        .chars()
+        .next_back()
+        .is_none_or(char::is_whitespace);
+    if !at_boundary || !partial.chars().all(is_tag_char) {
+        return None;
+    }
+    Some(partial.to_string())
+}
+
+/// Locations of every occurrence of `tag` or its nested tags across the
+/// collection.
+pub(crate) fn references(
+    state: &BackendState,
+    collection: &Collection,
+    tag: &str,
+) -> Vec<Location> {
+    let mut locations = Vec::new();
+    for (uri, _, text) in collection_utils::collection_texts(state, collection) {
+        for occurrence in find_tags(&text) {
+            if is_tag_or_descendant(&occurrence.name, tag) {
+                locations.push(Location {
+                    uri: uri.clone(),
+                    range: occurrence.range,
+                });
+            }
+        }
+    }
+    locations
+}
+
+/// Build a `WorkspaceEdit` renaming `old` to `new_name` in every note.
+///
+/// Nested tags move with their parent (`a/x` → `b/x` when renaming `a`).
+/// Body tags keep their `#`; frontmatter values keep their YAML form. When a
+/// note's `tags` already contain the new name, the old entry is removed
+/// instead of producing a duplicate.
+pub(crate) fn rename(
+    state: &BackendState,
+    collection: &Collection,
+    old: &str,
+    new_name: &str,
+) -> Option<WorkspaceEdit> {
+    let new_name = new_name.trim().trim_start_matches('#');
+    if new_name.is_empty() || !new_name.chars().all(is_tag_char) {
+        return None;
+    }
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for (uri, _, text) in collection_utils::collection_texts(state, collection) {
+        let occurrences = find_tags(&text);
+        let mut edits = Vec::new();
+        for occurrence in occurrences
+            .iter()
+            .filter(|t| is_tag_or_descendant(&t.name, old))
+        {
+            let renamed = format!("{}{}", new_name, &occurrence.name[old.len()..]);
+            let duplicate = renamed != occurrence.name
+                && occurrences
+                    .iter()
+                    .any(|t| t.in_frontmatter && t.name == renamed);
+            if occurrence.in_frontmatter && duplicate {
+                let line = text
+                    .lines()
+                    .nth(occurrence.range.start.line as usize)
+                    .unwrap_or("");
+                edits.push(TextEdit {
+                    range: removal_range(line, occurrence.range),
+                    new_text: String::new(),
+                });
+            } else {
+                // Only the `old` prefix changes; nested segments stay as-is.
+                let old_len = if occurrence.in_frontmatter {
+                    old.len()
+                } else {
+                    old.encode_utf16().count()
+                };
+                let start = occurrence.range.start;
+                edits.push(TextEdit {
+                    range: Range::new(
+                        start,
+                        Position::new(start.line, start.character + old_len as u32),
+                    ),
+                    new_text: new_name.to_string(),
+                });
+            }
+        }
+        if !edits.is_empty() {
+            changes.insert(uri, edits);
+        }
+    }
+
+    Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    })
+}
+
+/// Range removing a frontmatter tag value: the whole line for a block list
+/// item, otherwise the value (with its quotes) and one adjoining comma.
+fn removal_range(line: &str, range: Range) -> Range {
+    let line_idx = range.start.line;
+    if line.trim_start().starts_with('-') {
+        return Range::new(Position::new(line_idx, 0), Position::new(line_idx + 1, 0));
+    }
+    let mut start = range.start.character as usize;
+    let mut end = range.end.character as usize;
+    if start > 0 && line[..start].ends_with('#') {
+        start -= 1;
+    }
+    if start > 0 && line[..start].ends_with(['"', '\'']) {
+        start -= 1;
+        end += 1;
+    }
+    end = end.min(line.len());
+
+    if let Some(comma) = line[..start].rfind(',') {
+        if line[comma + 1..start].trim().is_empty() {
+            return Range::new(
+                Position::new(line_idx, comma as u32),
+                Position::new(line_idx, end as u32),
+            );
+        }
+    }
+    if let Some(rel) = line[end..].find(',') {
+        if line[end..end + rel].trim().is_empty() {
+            let after = end + rel + 1;
+            let next = after + (line[after..].len() - line[after..].trim_start().len());
+            return Range::new(
+                Position::new(line_idx, start as u32),
+                Position::new(line_idx, next as u32),
+            );
+        }
+    }
+    Range::new(
+        Position::new(line_idx, start as u32),
+        Position::new(line_idx, end as u32),
+    )
+}
+
+/// Hover contents for a tag: how many notes use it or one of its nested tags.
+pub(crate) fn hover(state: &BackendState, tag: &TagOccurrence) -> Hover {
+    let count = state
+        .file_index
+        .tag_counts()
+        .into_iter()
+        .find(|(name, _)| *name == tag.name)
+        .map(|(_, count)| count)
+        .unwrap_or(0);
+    let noun = if count == 1 { "note" } else { "notes" };
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("**Tag** `#{}`\n\nUsed in {} {}", tag.name, count, noun),
+        }),
+        range: Some(tag.range),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_frontmatter_and_body_tags() {
+        let text =
+            "---\ntags: [rust, \"#lsp\"]\n---\n# Heading\nSee #rust and `#code` #123 #a/b.\n";
+        let names: Vec<(String, bool)> = find_tags(text)
+            .into_iter()
+            .map(|t| (t.name, t.in_frontmatter))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("rust".to_string(), true),
+                ("lsp".to_string(), true),
+                ("rust".to_string(), false),
+                ("a/b".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn block_list_tags() {
+        let text = "---\ntags:\n  - one\n  - two\ntitle: x\n---\n";
+        let tags = find_tags(text);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[1].range.start, Position::new(3, 4));
+    }
+
+    #[test]
+    fn tag_hierarchy_helpers() {
+        assert!(is_tag_or_descendant("project/alpha", "project"));
+        assert!(is_tag_or_descendant("project", "project"));
+        assert!(!is_tag_or_descendant("projects", "project"));
+        let chain: Vec<&str> = tag_with_ancestors("a/b/c").collect();
+        assert_eq!(chain, vec!["a", "a/b", "a/b/c"]);
+        assert_eq!(
+            tag_prefix_at("see #project/al", 15).as_deref(),
+            Some("project/al")
+        );
+        assert_eq!(tag_prefix_at("a#b", 3), None);
+    }
+
+    #[test]
+    fn removal_range_in_flow_list() {
+        let line = "tags: [a, \"b\", c]";
+        let r = removal_range(line, Range::new(Position::new(1, 11), Position::new(1, 12)));
+        assert_eq!((r.start.character, r.end.character), (8, 13));
+        let r = removal_range(line, Range::new(Position::new(1, 7), Position::new(1, 8)));
+        assert_eq!((r.start.character, r.end.character), (7, 10));
+    }
+}
@@ -63,6 +63,12 @@ pub(crate) fn frontmatter_bounds(text: &str) -> Option<(usize, usize)> {
     Some((first_idx + 1, close_idx - 1))
 }
 
+/// Index of the first body line: the line after the closing `---`, or 0
+/// when there is no frontmatter.
+pub(crate) fn body_start_line(text: &str) -> usize {
+    frontmatter_bounds(text).map_or(0, |(_, end)| end + 2)
+}
+
 pub(crate) fn is_in_frontmatter(text: &str, line: usize) -> bool {
     match frontmatter_bounds(text) {
         Some((start, end)) => line >= start && line <= end,
@@ -119,7 +125,44 @@ pub(crate) fn find_field_range(
     (start_pos, end_pos)
 }
 
+/// The lines of a top-level frontmatter field — its `key:` line and any
+/// indented or list continuation lines — as a start and exclusive end.
+pub(crate) fn top_level_field_lines(text: &str, key: &str) -> Option<(usize, usize)> {
+    let (_, end) = frontmatter_bounds(text)?;
+    let start = top_level_key_line(text, key)?;
+    let continuation = text
+        .lines()
+        .take(end + 1)
+        .skip(start + 1)
+        .take_while(|line| {
+            line.starts_with(' ') || line.starts_with('\t') || line.starts_with("- ")
+        })
+        .count();
+    Some((start, start + 1 + continuation))
+}
+
+/// Line index of a top-level (unindented) `key:` in the frontmatter.
+pub(crate) fn top_level_key_line(text: &str, key: &str) -> Option<usize> {
+    let (start, end) = frontmatter_bounds(text)?;
+    text.lines()
+        .enumerate()
+        .skip(start)
+        .take(end + 1 - start)
+        .find(|(_, line)| {
+            line.strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|(idx, _)| idx)
+}
+
 pub(crate) fn word_at(line: &str, column: usize) -> Option<String> {
+    let (start, end) = word_range_at(line, column)?;
+    Some(line[start..end].to_string())
+}
+
+/// Byte range of the word under `column`, using the same word characters as
+/// [`word_at`].
+pub(crate) fn word_range_at(line: &str, column: usize) -> Option<(usize, usize)> {
     if line.is_empty() {
         return None;
     }
@@ -137,8 +180,70 @@ pub(crate) fn word_at(line: &str, column: usize) -> Option<String> {
     if start == end {
         None
     } else {
-        Some(line[start..end].to_string())
+        Some((start, end))
+    }
+}
+
+/// Split the YAML value on a frontmatter line into scalar tokens with their
+/// byte ranges, treating flow-list brackets and commas as separators.
+///
+/// `key: [a, "b c"]` → `a`, `b c`; `  - a` → `a`. Quoted scalars yield the
+/// range inside the quotes, and a ` #` comment ends the value.
+pub(crate) fn value_tokens(line: &str) -> Vec<(usize, usize)> {
+    let trimmed = line.trim_start();
+    let leading = line.len() - trimmed.len();
+    let value_start = if trimmed.starts_with('-') {
+        leading + 1
+    } else {
+        match line.find(':') {
+            Some(idx) => idx + 1,
+            None => return Vec::new(),
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut end = value_start;
+    let mut after_space = true;
+    let mut chars = line[value_start..]
+        .char_indices()
+        .map(|(idx, ch)| (value_start + idx, ch));
+    while let Some((idx, ch)) = chars.next() {
+        if (ch == '"' || ch == '\'') && start.is_none() {
+            let content_start = idx + ch.len_utf8();
+            let content_end = chars
+                .by_ref()
+                .find(|(_, c)| *c == ch)
+                .map(|(j, _)| j)
+                .unwrap_or(line.len());
+            if content_end > content_start {
+                tokens.push((content_start, content_end));
+            }
+            after_space = false;
+            continue;
+        }
+        if ch == '#' && after_space {
+            break;
+        }
+        if matches!(ch, '[' | ']' | ',') {
+            if let Some(s) = start.take() {
+                tokens.push((s, end));
+            }
+            after_space = true;
+            continue;
+        }
+        if ch.is_whitespace() {
+            after_space = true;
+            continue;
+        }
+        after_space = false;
+        start.get_or_insert(idx);
+        end = idx + ch.len_utf8();
+    }
+    if let Some(s) = start {
+        tokens.push((s, end));
     }
+    tokens
 }
 
 fn is_word_char(b: u8) -> bool {
@@ -267,6 +372,22 @@ pub(crate) fn link_at_position(text: &str, line_idx: usize, column: usize) -> Op
 // Frontmatter value extraction
 // ---------------------------------------------------------------------------
 
+/// Byte offset where the value starts on a `key: value` or `- value`
+/// frontmatter line — the text `value_from_frontmatter_line` returns.
+pub(crate) fn frontmatter_value_offset(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let leading = line.len() - trimmed.len();
+    let after = if let Some(colon_idx) = trimmed.find(':') {
+        leading + colon_idx + 1
+    } else if trimmed.starts_with('-') {
+        leading + 1
+    } else {
+        return None;
+    };
+    let rest = &line[after..];
+    Some(after + rest.len() - rest.trim_start().len())
+}
+
 /// Extract the value portion of a frontmatter line if the cursor is past the
 /// field delimiter.
 ///
@@ -414,3 +535,471 @@ pub(crate) fn field_name_for_position(text: &str, line_idx: usize) -> Option<Str
 
     None
 }
+
+// ---------------------------------------------------------------------------
+// Nested field paths
+// ---------------------------------------------------------------------------
+
+/// Where a frontmatter line sits in the YAML structure.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct FieldPath {
+    /// Keys of the enclosing mappings, outermost first. List items add no
+    /// segment: `people:\n  - name: x` gives `["people"]` for `name`.
+    pub parents: Vec<String>,
+    /// The key on this line, or `None` for a scalar list item or a key that
+    /// is still being typed (no colon yet).
+    pub key: Option<String>,
+    /// Whether the line starts with `- `.
+    pub in_list_item: bool,
+}
+
+impl FieldPath {
+    /// Full path of the value on this line: the parents plus the key.
+    pub fn segments(&self) -> Vec<String> {
+        let mut segments = self.parents.clone();
+        segments.extend(self.key.clone());
+        segments
+    }
+}
+
+/// Resolve the field path of `line_idx` by walking back through less
+/// indented lines. Handles nested mappings and lists of objects, including
+/// sequences written at the same indent as their parent key.
+pub(crate) fn field_path_at(text: &str, line_idx: usize) -> Option<FieldPath> {
+    let lines: Vec<&str> = text.lines().collect();
+    let line = lines.get(line_idx)?;
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+
+    let mut path = FieldPath::default();
+    let mut current_indent = indent;
+    let mut at_dash = false;
+    if let Some(item) = trimmed.strip_prefix('-') {
+        path.in_list_item = true;
+        path.key = key_of(item.trim_start());
+        at_dash = true;
+    } else {
+        path.key = key_of(trimmed);
+    }
+
+    let first_line = frontmatter_bounds(text)
+        .map(|(start, _)| start)
+        .unwrap_or(0);
+    let mut parents = Vec::new();
+    for prev_idx in (first_line..line_idx).rev() {
+        let prev = lines[prev_idx];
+        let prev_trimmed = prev.trim_start();
+        if prev_trimmed.is_empty() || prev_trimmed.starts_with('#') {
+            continue;
+        }
+        let prev_indent = prev.len() - prev_trimmed.len();
+        if prev_trimmed.starts_with('-') {
+            if prev_indent < current_indent {
+                // The list item whose mapping contains this line.
+                current_indent = prev_indent;
+                at_dash = true;
+            }
+            continue;
+        }
+        let opens_block = prev_trimmed.trim_end().ends_with(':');
+        let encloses = prev_indent < current_indent
+            || (at_dash && prev_indent == current_indent && opens_block);
+        if !encloses {
+            continue;
+        }
+        let Some(key) = key_of(prev_trimmed) else {
+            continue;
+        };
+        parents.push(key);
+        current_indent = prev_indent;
+        at_dash = false;
+        if prev_indent == 0 {
+            break;
+        }
+    }
+    parents.reverse();
+    path.parents = parents;
+    Some(path)
+}
+
+/// The key of a `key: value` fragment, unquoted.
+fn key_of(fragment: &str) -> Option<String> {
+    let colon_idx = fragment.find(':')?;
+    let key = fragment[..colon_idx].trim().trim_matches(['"', '\'']);
+    if key.is_empty() || key.starts_with('-') {
+        None
+    } else {
+        Some(key.to_string())
+    }
+}
+
+/// Split a validation issue field such as `address.city` or
+/// `people[0].name` into path segments, dropping list indices.
+pub(crate) fn split_field_path(field: &str) -> Vec<String> {
+    field
+        .split('.')
+        .map(|seg| seg.split('[').next().unwrap_or(seg).trim())
+        .filter(|seg| !seg.is_empty() && !seg.chars().all(|c| c.is_ascii_digit()))
+        .map(|seg| seg.to_string())
+        .collect()
+}
+
+/// Range of the first key in the frontmatter whose full path is `path`.
+pub(crate) fn find_field_path_range(
+    text: &str,
+    path: &[String],
+) -> Option<(
+    tower_lsp::lsp_types::Position,
+    tower_lsp::lsp_types::Position,
+)> {
+    let (start, end) = frontmatter_bounds(text)?;
+    for (line_idx, line) in text.lines().enumerate().take(end + 1).skip(start) {
+        let Some(found) = field_path_at(text, line_idx) else {
+            continue;
+        };
+        if found.key.is_none() || found.segments() != path {
+            continue;
+        }
+        let key = found.key.unwrap_or_default();
+        let start_col = line.find(key.as_str()).unwrap_or(0);
+        return Some((
+            tower_lsp::lsp_types::Position::new(line_idx as u32, start_col as u32),
+            tower_lsp::lsp_types::Position::new(line_idx as u32, (start_col + key.len()) as u32),
+        ));
+    }
+    None
+}
+
+/// Find the declaration of a nested field in a type definition: the top-level
+/// field under `fields:`, then each further segment among the direct
+/// children of the parent's `fields:` (or `items:` → `fields:`) block, so a
+/// sub-field named like a property (`type`, `required`) is not confused with
+/// the parent's own property.
+pub(crate) fn nested_field_declaration(text: &str, path: &[String]) -> Option<FieldDeclaration> {
+    let (first, rest) = path.split_first()?;
+    let mut current = type_field_declarations(text)
+        .into_iter()
+        .find(|decl| &decl.name == first)?;
+    let lines: Vec<&str> = text.lines().collect();
+    for segment in rest {
+        let fields = direct_child(&lines, current.line, "fields").or_else(|| {
+            let items = direct_child(&lines, current.line, "items")?;
+            direct_child(&lines, items.line, "fields")
+        })?;
+        current = direct_child(&lines, fields.line, segment)?;
+    }
+    Some(current)
+}
+
+/// The direct child `key:` of the mapping opened on `parent_line`; the first
+/// child line sets the indentation of its siblings.
+fn direct_child(lines: &[&str], parent_line: usize, key: &str) -> Option<FieldDeclaration> {
+    let parent = lines.get(parent_line)?;
+    let parent_indent = parent.len() - parent.trim_start().len();
+    let mut child_indent = None;
+    for (idx, line) in lines.iter().enumerate().skip(parent_line + 1) {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        if indent <= parent_indent || trimmed.trim_end() == "---" {
+            break;
+        }
+        if indent != *child_indent.get_or_insert(indent) {
+            continue;
+        }
+        if key_of(trimmed).as_deref() == Some(key) {
+            let start_col = indent + line[indent..].find(key).unwrap_or(0);
+            return Some(FieldDeclaration {
+                name: key.to_string(),
+                line: idx,
+                start_col,
+                end_col: start_col + key.len(),
+            });
+        }
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Type definition field declarations
+// ---------------------------------------------------------------------------
+
+/// A field declared under the top-level `fields:` mapping of a type definition.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldDeclaration {
+    pub name: String,
+    pub line: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+}
+
+/// Find the direct children of the top-level `fields:` key in a type
+/// definition (`.md` frontmatter or a plain `.yaml` file).
+pub(crate) fn type_field_declarations(text: &str) -> Vec<FieldDeclaration> {
+    let mut result = Vec::new();
+    let mut in_fields = false;
+    let mut child_indent: Option<usize> = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if !in_fields {
+            if indent == 0 && trimmed.trim_end() == "fields:" {
+                in_fields = true;
+            }
+            continue;
+        }
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if indent == 0 {
+            break;
+        }
+        let expected = *child_indent.get_or_insert(indent);
+        if indent != expected {
+            continue;
+        }
+        let Some(colon_idx) = trimmed.find(':') else {
+            continue;
+        };
+        let raw = trimmed[..colon_idx].trim_end();
+        let name = raw.trim_matches(['"', '\'']);
+        if name.is_empty() {
+            continue;
+        }
+        let start_col = indent + (raw.len() - raw.trim_start_matches(['"', '\'']).len());
+        result.push(FieldDeclaration {
+            name: name.to_string(),
+            line: line_idx,
+            start_col,
+            end_col: start_col + name.len(),
+        });
+    }
+
+    result
+}
+
+/// Byte offset of an LSP position (UTF-16 column) in `text`, clamped to the
+/// end of its line.
+pub(crate) fn byte_offset(text: &str, position: tower_lsp::lsp_types::Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        line_start += text[line_start..].find('\n')? + 1;
+    }
+    let line_end = text[line_start..]
+        .find('\n')
+        .map(|i| line_start + i)
+        .unwrap_or(text.len());
+    let mut units = 0;
+    for (idx, ch) in text[line_start..line_end].char_indices() {
+        if units >= position.character as usize {
+            return Some(line_start + idx);
+        }
+        units += ch.len_utf16();
+    }
+    Some(line_end)
+}
+
+/// An ATX heading (`## Title ##`) as its level and text, or `None` when the
+/// line is not a heading or the heading is empty.
+pub(crate) fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim();
+    let rest = trimmed.trim_start_matches('#');
+    let level = trimmed.len() - rest.len();
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim_end();
+    (!title.is_empty()).then_some((level, title))
+}
+
+/// `text` with non-overlapping `edits` applied. Edits starting at the same
+/// position land in array order, as in an LSP `TextEdit[]`.
+pub(crate) fn apply_edits(text: &str, edits: &[tower_lsp::lsp_types::TextEdit]) -> String {
+    let mut spans: Vec<(usize, usize, usize, &str)> = edits
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, edit)| {
+            let start = byte_offset(text, edit.range.start)?;
+            let end = byte_offset(text, edit.range.end)?;
+            Some((start, idx, end.max(start), edit.new_text.as_str()))
+        })
+        .collect();
+    spans.sort_by_key(|(start, idx, _, _)| std::cmp::Reverse((*start, *idx)));
+    let mut out = text.to_string();
+    for (start, _, end, new_text) in spans {
+        out.replace_range(start..end, new_text);
+    }
+    out
+}
+
+/// Longest paragraph returned by `first_paragraph`, in characters.
+const MAX_PARAGRAPH_CHARS: usize = 500;
+
+/// The first prose paragraph of a note body, skipping headings and fenced
+/// code. Long paragraphs are truncated.
+pub(crate) fn first_paragraph(body: &str) -> Option<String> {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            if !lines.is_empty() {
+                break;
+            }
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') && !lines.is_empty() {
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        lines.push(trimmed);
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    let paragraph = lines.join(" ");
+    if paragraph.chars().count() > MAX_PARAGRAPH_CHARS {
+        let mut cut: String = paragraph.chars().take(MAX_PARAGRAPH_CHARS).collect();
+        cut.push_str("...");
+        return Some(cut);
+    }
+    Some(paragraph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_field_declarations_lists_direct_children() {
+        let text = "---\nname: task\nextends: base\nfields:\n  status:\n    type: enum\n    values: [a, b]\n  \"due\":\n    type: date\n---\n";
+        let decls = type_field_declarations(text);
+        let names: Vec<&str> = decls.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["status", "due"]);
+        assert_eq!(decls[0].line, 4);
+        assert_eq!((decls[0].start_col, decls[0].end_col), (2, 8));
+        assert_eq!((decls[1].start_col, decls[1].end_col), (3, 6));
+    }
+
+    #[test]
+    fn value_tokens_handles_scalars_and_lists() {
+        let line = "types: [meeting, \"note\"]";
+        let tokens: Vec<&str> = value_tokens(line)
+            .into_iter()
+            .map(|(s, e)| &line[s..e])
+            .collect();
+        assert_eq!(tokens, vec!["meeting", "note"]);
+        assert_eq!(value_tokens("  - meeting"), vec![(4, 11)]);
+        assert_eq!(value_tokens("type: meeting # old"), vec![(6, 13)]);
+        assert_eq!(value_tokens("status: in progress"), vec![(8, 19)]);
+        assert_eq!(
+            value_tokens("values: [\"a, b\", c]"),
+            vec![(10, 14), (17, 18)]
+        );
+    }
+
+    #[test]
+    fn top_level_key_line_ignores_nested_keys() {
+        let text = "---\naddress:\n  due: x\ndue : 2024-01-01\n---\ndue: body\n";
+        assert_eq!(top_level_key_line(text, "due"), Some(3));
+        assert_eq!(top_level_key_line(text, "city"), None);
+    }
+
+    #[test]
+    fn field_paths_in_nested_mappings_and_lists() {
+        let text =
+            "---\naddress:\n  city: Oslo\npeople:\n  - name: A\n    role: B\nitems:\n- x\n---\n";
+        let path = |line| field_path_at(text, line).unwrap();
+        assert_eq!(path(2).segments(), vec!["address", "city"]);
+        assert_eq!(path(4).segments(), vec!["people", "name"]);
+        assert!(path(4).in_list_item);
+        assert_eq!(path(5).segments(), vec!["people", "role"]);
+        assert_eq!(path(7).segments(), vec!["items"]);
+        assert_eq!(path(7).key, None);
+        assert_eq!(split_field_path("people[0].name"), vec!["people", "name"]);
+        let range = find_field_path_range(text, &["people".into(), "role".into()]).unwrap();
+        assert_eq!((range.0.line, range.0.character), (5, 4));
+    }
+
+    #[test]
+    fn nested_declarations() {
+        let text = "---\nfields:\n  address:\n    type: object\n    fields:\n      city:\n        type: string\n---\n";
+        let decl = nested_field_declaration(text, &["address".into(), "city".into()]).unwrap();
+        assert_eq!((decl.line, decl.start_col, decl.end_col), (5, 6, 10));
+    }
+
+    #[test]
+    fn nested_declarations_skip_the_parents_own_properties() {
+        let text = "---\nfields:\n  address:\n    type: object\n    required: true\n    fields:\n      type:\n        type: string\n      required:\n        type: boolean\n  people:\n    type: list\n    items:\n      type: object\n      fields:\n        type:\n          type: string\n---\n";
+        let line = |path: &[&str]| {
+            let path: Vec<String> = path.iter().map(|s| s.to_string()).collect();
+            nested_field_declaration(text, &path).map(|d| d.line)
+        };
+        assert_eq!(line(&["address", "type"]), Some(6));
+        assert_eq!(line(&["address", "required"]), Some(8));
+        assert_eq!(line(&["people", "type"]), Some(15));
+        assert_eq!(line(&["address", "city"]), None);
+    }
+
+    #[test]
+    fn first_paragraph_skips_headings_and_code() {
+        let body = "\n# Title\n\n```\ncode\n```\nFirst line\nsecond line\n\nLater.\n";
+        assert_eq!(
+            first_paragraph(body).as_deref(),
+            Some("First line second line")
+        );
+        assert_eq!(first_paragraph("# Only a heading\n"), None);
+    }
+
+    #[test]
+    fn atx_heading_parses_level_and_title() {
+        assert_eq!(atx_heading("## Design notes ##"), Some((2, "Design notes")));
+        assert_eq!(atx_heading("# Title"), Some((1, "Title")));
+        assert_eq!(atx_heading("#tag"), None);
+        assert_eq!(atx_heading("####### deep"), None);
+        assert_eq!(atx_heading("## "), None);
+    }
+
+    #[test]
+    fn top_level_field_lines_include_continuations() {
+        let text = "---\ntags:\n  - a\n- b\nstatus: draft\n---\n";
+        assert_eq!(top_level_field_lines(text, "tags"), Some((1, 4)));
+        assert_eq!(top_level_field_lines(text, "status"), Some((4, 5)));
+    }
+
+    #[test]
+    fn apply_edits_replaces_from_the_end() {
+        use tower_lsp::lsp_types::{Position, Range, TextEdit};
+        let edit = |line, start, end, new_text: &str| TextEdit {
+            range: Range::new(Position::new(line, start), Position::new(line, end)),
+            new_text: new_text.to_string(),
+        };
+        let text = "see [[a]] and [[b]]\nend\n";
+        let edits = [edit(0, 4, 9, "[[x/a]]"), edit(0, 14, 19, "[[y]]")];
+        assert_eq!(apply_edits(text, &edits), "see [[x/a]] and [[y]]\nend\n");
+    }
+
+    #[test]
+    fn byte_offset_counts_utf16_units() {
+        use tower_lsp::lsp_types::Position;
+        let text = "ab\n😀x\nend";
+        assert_eq!(byte_offset(text, Position::new(1, 2)), Some(7));
+        assert_eq!(byte_offset(text, Position::new(1, 99)), Some(8));
+        assert_eq!(byte_offset(text, Position::new(2, 0)), Some(9));
+        assert_eq!(byte_offset(text, Position::new(5, 0)), None);
+    }
+}
@@ -0,0 +1,203 @@
+/// Type hierarchy provider — walks the `extends` chain of type definitions.
+///
+/// Items can be prepared from a `type:` value in a note or from inside a type
+/// definition file. Preparing on a field declaration in a type file carries the
+/// field along, so supertypes show where it was inherited from and subtypes
+/// show which child types override it.
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::collection_utils;
+use crate::state::BackendState;
+use crate::text;
+
+/// Build the `textDocument/prepareTypeHierarchy` response.
+pub(crate) fn prepare(
+    state: &BackendState,
+    params: TypeHierarchyPrepareParams,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let collection = state.get_collection()?;
+    let uri = &params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+    let text = state.document_text(uri)?;
+    let line_idx = position.line as usize;
+    let column = position.character as usize;
+    let line_text = text.lines().nth(line_idx).unwrap_or("");
+
+    // Inside a type definition file
+    if let Some(type_name) = collection_utils::type_name_for_uri(&collection, uri) {
+        if text::field_name_from_line(line_text).as_deref() == Some("extends") {
+            let colon_idx = line_text.find(':').unwrap_or(0);
+            if column > colon_idx {
+                let parent = text::word_at(line_text, column)?.to_lowercase();
+                return Some(vec![item_for(state, &collection, &parent, None)?]);
+            }
+        }
+        let field = text::type_field_declarations(&text)
+            .into_iter()
+            .find(|decl| decl.line == line_idx)
+            .map(|decl| decl.name);
+        return Some(vec![item_for(
+            state,
+            &collection,
+            &type_name,
+            field.as_deref(),
+        )?]);
+    }
+
+    // On a `type:` / `types:` value in a note
+    if !text::is_in_frontmatter(&text, line_idx) {
+        return None;
+    }
+    let field_name = text::field_name_for_position(&text, line_idx)?;
+    if !collection_utils::is_type_key(&collection, &field_name) {
+        return None;
+    }
+    text::value_from_frontmatter_line(line_text, column)?;
+    let type_name = text::word_at(line_text, column)?.to_lowercase();
+    Some(vec![item_for(state, &collection, &type_name, None)?])
+}
+
+/// Build the `typeHierarchy/supertypes` response.
+///
+/// Without a field this is the direct `extends` parent. With a field it is
+/// the nearest ancestor that declares that field.
+pub(crate) fn supertypes(
+    state: &BackendState,
+    params: TypeHierarchySupertypesParams,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let collection = state.get_collection()?;
+    let (type_name, field) = item_data(&collection, &params.item)?;
+    let type_def = collection.types.get(&type_name)?;
+    let type_decls = collection_utils::TypeDeclarations::new(&collection);
+
+    let mut visited = HashSet::from([type_name.clone()]);
+    let mut current = type_def.extends.as_ref().map(|p| p.to_lowercase());
+    while let Some(name) = current {
+        if !visited.insert(name.clone()) {
+            break;
+        }
+        let Some(parent_def) = collection.types.get(&name) else {
+            break;
+        };
+        match field.as_deref() {
+            Some(field) if !type_decls.declares(&name, field) => {
+                current = parent_def.extends.as_ref().map(|p| p.to_lowercase());
+            }
+            _ => {
+                return Some(
+                    item_for(state, &collection, &name, field.as_deref())
+                        .into_iter()
+                        .collect(),
+                );
+            }
+        }
+    }
+    Some(Vec::new())
+}
+
+/// Build the `typeHierarchy/subtypes` response.
+///
+/// Without a field these are the types extending the item directly. With a
+/// field they are the nearest descendants that redeclare (override) it.
+pub(crate) fn subtypes(
+    state: &BackendState,
+    params: TypeHierarchySubtypesParams,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let collection = state.get_collection()?;
+    let (type_name, field) = item_data(&collection, &params.item)?;
+
+    let type_decls = collection_utils::TypeDeclarations::new(&collection);
+
+    let mut result = Vec::new();
+    let mut visited = HashSet::from([type_name.clone()]);
+    let mut pending = collection_utils::child_types(&collection, &type_name);
+    while let Some(child) = pending.pop() {
+        if !visited.insert(child.clone()) {
+            continue;
+        }
+        if !collection.types.contains_key(&child) {
+            continue;
+        }
+        match field.as_deref() {
+            Some(field) if !type_decls.declares(&child, field) => {
+                pending.extend(collection_utils::child_types(&collection, &child));
+            }
+            _ => {
+                if let Some(item) = item_for(state, &collection, &child, field.as_deref()) {
+                    result.push(item);
+                }
+            }
+        }
+    }
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(result)
+}
+
+/// Build a type hierarchy item pointing at a type's definition file.
+///
+/// When `field` is given the selection range points at its declaration.
+fn item_for(
+    state: &BackendState,
+    collection: &Collection,
+    type_name: &str,
+    field: Option<&str>,
+) -> Option<TypeHierarchyItem> {
+    let type_def = collection.types.get(type_name)?;
+    let path = collection_utils::find_type_definition_path(collection, type_name)?;
+    let uri = Url::from_file_path(&path).ok()?;
+
+    let mut selection_range = Range::new(Position::new(0, 0), Position::new(0, 0));
+    if let Some(field) = field {
+        let type_text = state
+            .document_text(&uri)
+            .or_else(|| std::fs::read_to_string(&path).ok())
+            .unwrap_or_default();
+        if let Some(decl) = text::type_field_declarations(&type_text)
+            .into_iter()
+            .find(|decl| decl.name == field)
+        {
+            selection_range = Range::new(
+                Position::new(decl.line as u32, decl.start_col as u32),
+                Position::new(decl.line as u32, decl.end_col as u32),
+            );
+        }
+    }
+
+    let detail = match field {
+        Some(field) => Some(format!("declares `{}`", field)),
+        None => type_def.description.clone(),
+    };
+    Some(TypeHierarchyItem {
+        name: type_def.name.clone(),
+        kind: SymbolKind::CLASS,
+        tags: None,
+        detail,
+        uri,
+        range: selection_range,
+        selection_range,
+        data: Some(serde_json::json!({ "type": type_name, "field": field })),
+    })
+}
+
+/// Recover the type name (and optional field) carried by an item, falling back
+/// to the type defined by the item's file.
+fn item_data(
+    collection: &Collection,
+    item: &TypeHierarchyItem,
+) -> Option<(String, Option<String>)> {
+    let data = item.data.as_ref();
+    let type_name = data
+        .and_then(|d| d.get("type"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .or_else(|| collection_utils::type_name_for_uri(collection, &item.uri))?;
+    let field = data
+        .and_then(|d| d.get("field"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    Some((type_name, field))
+}
@@ -0,0 +1,336 @@
+/// Type usage lookup — resolves type names under the cursor and finds the
+/// notes that belong to a type.
+use tower_lsp::lsp_types::*;
+
+use mdbase::Collection;
+
+use crate::body_links;
+use crate::collection_utils;
+use crate::state::BackendState;
+use crate::text;
+
+/// A type name found at the cursor.
+#[derive(Debug, Clone)]
+pub(crate) struct TypeAtCursor {
+    /// Collection type key (lowercase).
+    pub name: String,
+    /// Range of the name in the document, or an empty range at the cursor
+    /// when it is elsewhere in a type definition file.
+    pub range: Range,
+}
+
+/// Find the type referenced at `position`.
+///
+/// Handles `type:` / `types:` (and explicit type key) values in a note,
+/// `extends:` values in a type definition, and anywhere else inside a type
+/// definition file (meaning the type it defines).
+pub(crate) fn type_at_position(
+    collection: &Collection,
+    uri: &Url,
+    text: &str,
+    position: Position,
+) -> Option<TypeAtCursor> {
+    let line_idx = position.line as usize;
+    let column = position.character as usize;
+    let line_text = text.lines().nth(line_idx).unwrap_or("");
+
+    if let Some(type_name) = collection_utils::type_name_for_uri(collection, uri) {
+        if body_links::body_link_at(text, line_idx, column).is_some() {
+            return None;
+        }
+        let key = text::field_name_from_line(line_text);
+        if key.as_deref() == Some("extends") || key.as_deref() == Some("name") {
+            if let Some(found) = type_value_at(collection, line_text, line_idx, column) {
+                return Some(found);
+            }
+        }
+        return Some(TypeAtCursor {
+            name: type_name,
+            range: Range::new(position, position),
+        });
+    }
+
+    if !text::is_in_frontmatter(text, line_idx) {
+        return None;
+    }
+    if !is_type_key_line(collection, text, line_idx) {
+        return None;
+    }
+    type_value_at(collection, line_text, line_idx, column)
+}
+
+/// Whether `line_idx` holds the value of a top-level type key: `type: x`,
+/// `types: [x]`, or an item of a block list under one (indented or not).
+/// Nested keys that happen to be called `type` do not count.
+fn is_type_key_line(collection: &Collection, text: &str, line_idx: usize) -> bool {
+    top_level_key_at(text, line_idx)
+        .is_some_and(|key| collection_utils::is_type_key(collection, &key))
+}
+
+/// The top-level frontmatter key whose value covers `line_idx`, or `None`
+/// when the line belongs to a nested field.
+fn top_level_key_at(text: &str, line_idx: usize) -> Option<String> {
+    let path = text::field_path_at(text, line_idx)?;
+    match path.segments().as_slice() {
+        [key] => Some(key.clone()),
+        _ => None,
+    }
+}
+
+/// Resolve the word under the cursor in a frontmatter value to a known type.
+fn type_value_at(
+    collection: &Collection,
+    line_text: &str,
+    line_idx: usize,
+    column: usize,
+) -> Option<TypeAtCursor> {
+    text::value_from_frontmatter_line(line_text, column)?;
+    let (start, end) = text::word_range_at(line_text, column)?;
+    let name = line_text[start..end].to_lowercase();
+    if !collection.types.contains_key(&name) {
+        return None;
+    }
+    Some(TypeAtCursor {
+        name,
+        range: Range::new(
+            Position::new(line_idx as u32, start as u32),
+            Position::new(line_idx as u32, end as u32),
+        ),
+    })
+}
+
+/// List every indexed note whose types include `type_name`.
+///
+/// Each location points at the note's type key when it has one, otherwise at
+/// the start of the file.
+pub(crate) fn documents_of_type(
+    state: &BackendState,
+    collection: &Collection,
+    type_name: &str,
+) -> Vec<Location> {
+    let mut entries: Vec<_> = state
+        .file_index
+        .all_entries()
+        .into_iter()
+        .filter(|e| e.types.iter().any(|t| t.eq_ignore_ascii_case(type_name)))
+        .collect();
+    entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+
+    let mut locations = Vec::new();
+    for entry in entries {
+        let Some(uri) = collection_utils::uri_from_rel_path(collection, &entry.rel_path) else {
+            continue;
+        };
+        let text = state
+            .document_text(&uri)
+            .or_else(|| std::fs::read_to_string(collection.root.join(&entry.rel_path)).ok())
+            .unwrap_or_default();
+        locations.push(Location {
+            uri,
+            range: type_key_range(collection, &text),
+        });
+    }
+    locations
+}
+
+/// Range of the first type key (`type`, `types`, or an explicit type key) in
+/// a note's frontmatter, or the start of the file when there is none.
+fn type_key_range(collection: &Collection, text: &str) -> Range {
+    let keys = collection
+        .settings
+        .explicit_type_keys
+        .iter()
+        .map(|k| k.as_str())
+        .chain(["type", "types"]);
+    for key in keys {
+        let (start, end) = text::find_field_range(text, key, 0);
+        if start != end {
+            return Range::new(start, end);
+        }
+    }
+    Range::new(Position::new(0, 0), Position::new(0, 0))
+}
+
+/// Build the `textDocument/implementation` response: every note of the type
+/// under the cursor.
+pub(crate) fn implementation(
+    state: &BackendState,
+    uri: &Url,
+    position: Position,
+) -> Option<GotoDefinitionResponse> {
+    let collection = state.get_collection()?;
+    let text = state.document_text(uri)?;
+    let found = type_at_position(&collection, uri, &text, position)?;
+    let locations = documents_of_type(state, &collection, &found.name);
+    if locations.is_empty() {
+        None
+    } else {
+        Some(GotoDefinitionResponse::Array(locations))
+    }
+}
+
+/// Build a `WorkspaceEdit` renaming a type across the collection.
+///
+/// Renames the type definition file, rewrites its `name:`, every `extends:`
+/// and link field `target:` naming it, and every type key value in notes.
+pub(crate) fn rename(
+    state: &BackendState,
+    collection: &Collection,
+    old_name: &str,
+    new_name: &str,
+) -> Option<WorkspaceEdit> {
+    let new_name = new_name.trim();
+    if new_name.is_empty()
+        || new_name.contains(|c: char| c.is_whitespace() || matches!(c, '/' | ':' | '\\'))
+    {
+        return None;
+    }
+    if !new_name.eq_ignore_ascii_case(old_name)
+        && collection.types.contains_key(&new_name.to_lowercase())
+    {
+        return None;
+    }
+    let old_path = collection_utils::find_type_definition_path(collection, old_name)?;
+    let old_uri = Url::from_file_path(&old_path).ok()?;
+
+    let mut operations = Vec::new();
+
+    // Type definitions: own `name:`, children's `extends:`, link `target:`s.
+    let mut type_names: Vec<&String> = collection.types.keys().collect();
+    type_names.sort();
+    for type_name in type_names {
+        let Some(path) = collection_utils::find_type_definition_path(collection, type_name) else {
+            continue;
+        };
+        let Ok(uri) = Url::from_file_path(&path) else {
+            continue;
+        };
+        let type_text = state
+            .document_text(&uri)
+            .or_else(|| std::fs::read_to_string(&path).ok())
+            .unwrap_or_default();
+        let is_self = uri == old_uri;
+        let edits = type_definition_edits(&type_text, old_name, new_name, is_self);
+        if !edits.is_empty() {
+            operations.push(collection_utils::text_document_edit(uri, edits));
+        }
+    }
+
+    // Notes: values of `type`, `types`, and explicit type keys.
+    for (uri, _, note_text) in collection_utils::collection_texts(state, collection) {
+        let edits = note_type_edits(collection, &note_text, old_name, new_name);
+        if !edits.is_empty() {
+            operations.push(collection_utils::text_document_edit(uri, edits));
+        }
+    }
+
+    let extension = old_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("md");
+    let new_path = old_path.with_file_name(format!("{}.{}", new_name, extension));
+    if new_path != old_path {
+        let new_uri = Url::from_file_path(&new_path).ok()?;
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+            RenameFile {
+                old_uri,
+                new_uri,
+                options: None,
+                annotation_id: None,
+            },
+        )));
+    }
+
+    Some(WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    })
+}
+
+/// Edits to a type definition for a type rename: `extends:` and `target:`
+/// values naming the old type, plus `name:` in the renamed type's own file.
+fn type_definition_edits(
+    text: &str,
+    old_name: &str,
+    new_name: &str,
+    is_self: bool,
+) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+    let line_range = match text::frontmatter_bounds(text) {
+        Some((start, end)) => start..end + 1,
+        None => 0..text.lines().count(),
+    };
+    for (line_idx, line) in text.lines().enumerate() {
+        if !line_range.contains(&line_idx) {
+            continue;
+        }
+        let Some(key) = text::field_name_from_line(line) else {
+            continue;
+        };
+        let renames_here = match key.as_str() {
+            "extends" | "target" => true,
+            "name" => is_self && !line.starts_with(char::is_whitespace),
+            _ => false,
+        };
+        if renames_here {
+            edits.extend(token_edits(line, line_idx, old_name, new_name));
+        }
+    }
+    edits
+}
+
+/// Edits to a note's type key values (`type: x`, `types: [x]`, list items).
+fn note_type_edits(
+    collection: &Collection,
+    text: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+    let Some((start, end)) = text::frontmatter_bounds(text) else {
+        return edits;
+    };
+    for (line_idx, line) in text.lines().enumerate() {
+        if line_idx < start || line_idx > end {
+            continue;
+        }
+        if is_type_key_line(collection, text, line_idx) {
+            edits.extend(token_edits(line, line_idx, old_name, new_name));
+        }
+    }
+    edits
+}
+
+/// Replace every value token on `line` equal (case-insensitively) to `old`.
+fn token_edits(line: &str, line_idx: usize, old: &str, new: &str) -> Vec<TextEdit> {
+    text::value_tokens(line)
+        .into_iter()
+        .filter(|(s, e)| line[*s..*e].eq_ignore_ascii_case(old))
+        .map(|(s, e)| TextEdit {
+            range: Range::new(
+                Position::new(line_idx as u32, s as u32),
+                Position::new(line_idx as u32, e as u32),
+            ),
+            new_text: new.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_indent_list_items_belong_to_the_top_level_key() {
+        let text = "---\ntypes:\n- task\n- project\n---\n";
+        assert_eq!(top_level_key_at(text, 2).as_deref(), Some("types"));
+        assert_eq!(top_level_key_at(text, 3).as_deref(), Some("types"));
+    }
+
+    #[test]
+    fn nested_type_keys_are_not_top_level() {
+        let text = "---\ntype: task\naddress:\n  type: home\n---\n";
+        assert_eq!(top_level_key_at(text, 1).as_deref(), Some("type"));
+        assert_eq!(top_level_key_at(text, 3), None);
+    }
+}